@@ -2,6 +2,7 @@
 /// E0001: ca deve ser um u32 ( e não '{}').
 /// E0002: ca {} nao encontrado.
 /// E0003: ca deve ser especificado.
+/// E0004: erro ao consultar o ca {}: {}
 #[macro_export]
 macro_rules! erro {
     (1, $ca:expr) => {
@@ -13,4 +14,7 @@ macro_rules! erro {
     (3) => {
         "E0003: ca deve ser especificado."
     };
+    (4, $ca:expr, $erro:expr) => {
+        format!("E0004: erro ao consultar o ca {}: {}", $ca, $erro)
+    };
 }
@@ -1,12 +1,43 @@
-use actix_web::{middleware::Logger, App, HttpServer};
+use actix_web::{middleware::Logger, web, App, HttpServer};
+use cascraper::{ClienteConsultaCA, LimiteRequisicoes};
+use std::env;
+use std::str::FromStr;
 pub mod errors;
 mod views;
 use views::view_factory;
 
+/// Lê uma configuração do ambiente, usando `padrao` se ela não existir.
+fn configuracao<T: FromStr>(nome: &str, padrao: T) -> T {
+    match env::var(nome) {
+        Ok(valor) => valor
+            .trim()
+            .parse()
+            .unwrap_or_else(|_| panic!("{nome} inválido: '{valor}'")),
+        Err(_) => padrao,
+    }
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    HttpServer::new(|| App::new().wrap(Logger::default()).configure(view_factory))
-        .bind(("0.0.0.0", 8000))?
-        .run()
-        .await
+    // um só cliente para todos os workers, para compartilhar as conexões
+    // e o limite de requisições
+    let padrao = LimiteRequisicoes::default();
+    let limite = LimiteRequisicoes::new(
+        configuracao("CONSULTACA_POR_SEGUNDO", padrao.por_segundo),
+        configuracao("CONSULTACA_RAJADA", padrao.rajada),
+    );
+    let cliente = ClienteConsultaCA::builder()
+        .limite(limite)
+        .build()
+        .unwrap_or_else(|e| panic!("{e}"));
+    let cliente = web::Data::new(cliente);
+    HttpServer::new(move || {
+        App::new()
+            .app_data(cliente.clone())
+            .wrap(Logger::default())
+            .configure(view_factory)
+    })
+    .bind(("0.0.0.0", 8000))?
+    .run()
+    .await
 }
@@ -1,8 +1,12 @@
 use crate::erro;
 use actix_web::{web, HttpRequest, Responder};
+use cascraper::ClienteConsultaCA;
 use serde_json::json;
 
-pub async fn parse_ca_info(req: HttpRequest) -> impl Responder {
+pub async fn parse_ca_info(
+    req: HttpRequest,
+    cliente: web::Data<ClienteConsultaCA>,
+) -> impl Responder {
     let ca: u32 = match req.match_info().get("ca") {
         Some(c) => match c.parse() {
             Ok(v) => v,
@@ -10,14 +14,16 @@ pub async fn parse_ca_info(req: HttpRequest) -> impl Responder {
         },
         None => return web::Json(json!({"erro": erro!(3)})),
     };
-    let pagina = cascraper::pagina(None, ca).await;
-    let ca_info = match cascraper::CA::consultar(&pagina, ca).await {
+    let (ca_info, relatorio) = match cliente.consultar_com_relatorio(ca).await {
         Ok(v) => v,
         Err(e) => match e {
             cascraper::errors::CAError::NaoEncontrado(ca) => {
                 return web::Json(json!({"erro": erro!(2, ca)}))
             }
+            e => return web::Json(json!({"erro": erro!(4, ca, e)})),
         },
     };
-    web::Json(json!(ca_info))
+    let mut resposta = json!(ca_info);
+    resposta["relatorio"] = json!(relatorio);
+    web::Json(resposta)
 }
@@ -0,0 +1,117 @@
+/*!
+Avaliações dos usuários do consultaca.com, tanto do CA quanto do fabricante.
+*/
+use regex::Regex;
+use rust_decimal::Decimal;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+/// Nota do plugin raty: `raty({readOnly: true,hint: false,score: 4.5})`
+/// ou `data-score="4.5"`.
+static NOTA: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?:score:\s*|data-score="?)(\d+(?:[.,]\d+)?)"#).unwrap());
+/// Quantidade de avaliações: `(3 avaliações)`.
+static QUANTIDADE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)(\d+)\s*avalia(?:ç|c)(?:ão|ões|ao|oes)").unwrap());
+
+/// Avaliação média dada pelos usuários do site.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Avaliacao {
+    /// Ninguém avaliou ainda.
+    SemAvaliacoes,
+    /// Nota média (de 0 a 5) e, quando a página informa, o número de avaliações.
+    Nota {
+        media: Decimal,
+        quantidade: Option<u32>,
+    },
+}
+
+impl Avaliacao {
+    /// Interpreta um bloco de avaliação da página:
+    /// ```html
+    /// <div id="box_avaliacao">
+    ///   <div id="avaliacaoEPI"></div>
+    ///   <p class="valor">... <script>$('#avaliacaoEPI').raty({readOnly: true,hint: false,score: 0});</script></p>
+    /// </div>
+    /// <div class="box-avaliacao-fabricante">
+    ///   <span class="nenhuma_avaliacao">Ainda não foi realizada nenhuma avaliação ...</span>
+    /// </div>
+    /// ```
+    /// Nota zero sem quantidade de avaliações é `SemAvaliacoes`, pois é
+    /// assim que o site mostra um EPI que ninguém avaliou.
+    pub(crate) fn do_bloco(bloco: ElementRef) -> Result<Self, String> {
+        let nenhuma_selector = Selector::parse(".nenhuma_avaliacao").unwrap();
+        if bloco.select(&nenhuma_selector).next().is_some() {
+            return Ok(Avaliacao::SemAvaliacoes);
+        }
+        let html = bloco.html();
+        let nota = NOTA
+            .captures(&html)
+            .ok_or("nota não encontrada")?
+            .get(1)
+            .unwrap()
+            .as_str()
+            .replace(',', ".");
+        let media = Decimal::from_str(&nota).map_err(|e| e.to_string())?;
+        let quantidade = QUANTIDADE
+            .captures(&bloco.text().collect::<String>())
+            .and_then(|c| c[1].parse::<u32>().ok());
+        if media.is_zero() && quantidade.unwrap_or(0) == 0 {
+            return Ok(Avaliacao::SemAvaliacoes);
+        }
+        Ok(Avaliacao::Nota { media, quantidade })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::Html;
+
+    fn avaliacao(html: &str) -> Result<Avaliacao, String> {
+        let fragmento = Html::parse_fragment(html);
+        let bloco = fragmento
+            .select(&Selector::parse("div").unwrap())
+            .next()
+            .unwrap();
+        Avaliacao::do_bloco(bloco)
+    }
+
+    #[test]
+    fn blocos_de_avaliacao() {
+        assert_eq!(
+            avaliacao(
+                r#"<div><p class="valor"><script>$('#a').raty({readOnly: true,hint: false,score: 0});</script></p></div>"#
+            ),
+            Ok(Avaliacao::SemAvaliacoes)
+        );
+        assert_eq!(
+            avaliacao(
+                r#"<div><p class="valor">(12 avaliações)<script>$('#a').raty({readOnly: true,hint: false,score: 4.5});</script></p></div>"#
+            ),
+            Ok(Avaliacao::Nota {
+                media: Decimal::new(45, 1),
+                quantidade: Some(12)
+            })
+        );
+        assert_eq!(
+            avaliacao(r#"<div><span data-score="3,0"></span></div>"#),
+            Ok(Avaliacao::Nota {
+                media: Decimal::new(30, 1),
+                quantidade: None
+            })
+        );
+        assert_eq!(
+            avaliacao(
+                r#"<div class="box-avaliacao-fabricante"><span class="nenhuma_avaliacao">Ainda não foi realizada nenhuma avaliação.</span></div>"#
+            ),
+            Ok(Avaliacao::SemAvaliacoes)
+        );
+        assert_eq!(
+            avaliacao("<div><p>Avalie</p></div>"),
+            Err("nota não encontrada".to_string())
+        );
+    }
+}
@@ -1,197 +1,925 @@
 /*!
 Extrai informações do website do ConsultaCA e as usa para popular o struct CA.
 */
+use crate::avaliacao::Avaliacao;
+use crate::cnpj::Cnpj;
+use crate::duvida::Duvida;
 use crate::errors::CAError;
-use crate::util;
-use chrono::NaiveDate;
+use crate::grupo::GrupoEpi;
+use crate::historico::{EventoHistorico, Ocorrencia};
+use crate::limite::{Limitador, LimiteRequisicoes};
+use crate::modelo;
+use crate::natureza::Natureza;
+use crate::norma::NormaTecnica;
+use crate::pagina_ca::{self, PaginaCA};
+use crate::relatorio::{ExtracaoRelatorio, Problema, TipoProblema};
+use crate::retentativa::{retry_after, Falha, PoliticaRetentativa};
+use crate::situacao::Situacao;
+use crate::util::normalizar;
+use crate::validade::{self, Validade};
+use chrono::{NaiveDate, Utc};
 use log::{error, warn};
-use reqwest::Client;
+use regex::Regex;
+use reqwest::{Client, Url};
 use scraper::selectable::Selectable;
-use scraper::{ElementRef, Html, Selector};
+use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use util::extrair_numeros;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::LazyLock;
+
+/// Endereço padrão do website do ConsultaCA.
+pub const URL_BASE: &str = "https://consultaca.com/";
+
+/// Chaves de cada seção que viram campos do `CA`. As demais vão para
+/// `CA::extras`. A seção de título vazio é o cabeçalho da página.
+const CHAVES_CONHECIDAS: &[(&str, &[&str])] = &[
+    (
+        "",
+        &["n° ca", "situação", "validade", "n° processo", "natureza"],
+    ),
+    (
+        "dados complementares",
+        &[
+            "cor",
+            "marcação",
+            "referências",
+            "aprovado para",
+            "observação",
+        ],
+    ),
+    (
+        "fabricante",
+        &["razão social", "cnpj", "nome fantasia", "cidade/uf"],
+    ),
+    (
+        "laudos",
+        &["n° do laudo", "cnpj do laboratório", "razão social"],
+    ),
+];
+
+/// Nome do cabeçalho da página em `CA::extras`.
+const EXTRAS_CABECALHO: &str = "Cabeçalho";
+
+/// Limite compartilhado por todas as chamadas de `pagina`.
+static LIMITADOR_PADRAO: LazyLock<Limitador> =
+    LazyLock::new(|| Limitador::new(LimiteRequisicoes::default()));
 
 /// Pega o HTML da página do website do ConsultaCA.
-pub async fn pagina(client: Option<Client>, ca: u32) -> Html {
-    let client = match client {
-        Some(c) => c,
-        None => Client::new(),
-    };
+/// Falhas de rede e status fora da faixa 2xx viram `CAError`. Erros
+/// temporários são repetidos conforme `PoliticaRetentativa::default()`, e
+/// todas as chamadas, de qualquer `client`, respeitam juntas o
+/// `LimiteRequisicoes::default()`. Para outro limite, use
+/// `ClienteConsultaCA::pagina`.
+pub async fn pagina(client: Option<Client>, ca: u32) -> Result<Html, CAError> {
+    let client = client.unwrap_or_default();
+    let politica = PoliticaRetentativa::default();
+    let (body_txt, _) =
+        corpo_da_pagina(&client, URL_BASE, ca, &politica, Some(&LIMITADOR_PADRAO)).await?;
+    Ok(Html::parse_document(&body_txt))
+}
+
+/// Baixa o HTML da página do CA em `url_base`, sem interpretá-lo. Retorna
+/// também o número de tentativas feitas.
+pub(crate) async fn corpo_da_pagina(
+    client: &Client,
+    url_base: &str,
+    ca: u32,
+    politica: &PoliticaRetentativa,
+    limitador: Option<&Limitador>,
+) -> Result<(String, u32), CAError> {
+    let url = url_base.to_owned() + &ca.to_string();
+    baixar(client, &url, ca, politica, limitador).await
+}
+
+/// Baixa `url`, repetindo a requisição conforme a `politica` e esperando
+/// o `limitador` antes de cada tentativa. Toda requisição ao site deve
+/// passar por aqui. `ca` é o CA usado nos erros. Retorna o corpo e o
+/// número de tentativas feitas.
+pub(crate) async fn baixar(
+    client: &Client,
+    url: &str,
+    ca: u32,
+    politica: &PoliticaRetentativa,
+    limitador: Option<&Limitador>,
+) -> Result<(String, u32), CAError> {
+    politica
+        .executar(|| async {
+            if let Some(limitador) = limitador {
+                limitador.aguardar().await;
+            }
+            tentar(client, url, ca).await
+        })
+        .await
+}
+
+/// Uma única tentativa de baixar `url`.
+async fn tentar(client: &Client, url: &str, ca: u32) -> Result<String, Falha> {
     let resp = client
-        .get("https://consultaca.com/".to_owned() + &ca.to_string())
+        .get(url)
         .send()
-        .await;
-    let body_txt = match resp {
-        Ok(r) => match r.text().await {
-            Ok(txt) => txt,
-            Err(e) => panic!("{:#?}", e),
-        },
-        Err(e) => panic!("{}", e),
-    };
-    Html::parse_document(&body_txt)
+        .await
+        .map_err(|e| erro_de_rede(ca, e))?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(Falha {
+            erro: CAError::StatusHttp(ca, status.as_u16()),
+            retry_after: retry_after(resp.headers(), Utc::now()),
+        });
+    }
+    let body_txt = resp.text().await.map_err(|e| match erro_de_rede(ca, e) {
+        CAError::Conexao(ca, msg) => CAError::Corpo(ca, msg),
+        e => e,
+    })?;
+    Ok(body_txt)
+}
+
+/// Converte um erro do `reqwest` em `CAError`.
+fn erro_de_rede(ca: u32, e: reqwest::Error) -> CAError {
+    if e.is_timeout() {
+        CAError::TempoEsgotado(ca)
+    } else if e.is_decode() || e.is_body() {
+        CAError::Corpo(ca, e.to_string())
+    } else {
+        CAError::Conexao(ca, e.to_string())
+    }
 }
 
 /// Representa um CA.
 /// A única coisa que o struct tem de saber é o código do CA.
 /// O resto das informações será retirado do sítio https://consultaca.com/.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct CA {
-    descricao: String,
-    grupo: String,
-    natureza: String,
-    validade: chrono::NaiveDate,
-    descricao_completa: String,
-    situacao: String,
-    processo: u64,
-    aprovado_para: String,
+    descricao: Option<String>,
+    grupo: Option<GrupoEpi>,
+    grupo_id: Option<u32>,
+    natureza: Option<Natureza>,
+    validade: Option<Validade>,
+    descricao_completa: Option<String>,
+    situacao: Option<Situacao>,
+    processo: Option<u64>,
+    aprovado_para: Option<String>,
     cores: Vec<String>,
-    marcacao: String,
-    referencias: String,
-    normas: Vec<String>,
+    marcacao: Option<String>,
+    referencias: Option<String>,
+    modelos: Vec<String>,
+    observacao: Option<String>,
+    normas: Vec<NormaTecnica>,
     pub ca: u32,
-    laudo: Laudo,
+    laudos: Vec<Laudo>,
     fabricante: Fabricante,
+    historico: Vec<EventoHistorico>,
+    avaliacao: Option<Avaliacao>,
+    /// Fora da serialização até ser conferido numa página real (veja o
+    /// módulo `duvida`).
+    #[serde(skip)]
+    duvidas: Vec<Duvida>,
+    extras: BTreeMap<String, BTreeMap<String, String>>,
 }
 impl CA {
     /// Consulta a página do website do ConsultaCA e popula uma instância do struct CA.
-    pub async fn consultar(body: &Html, ca: u32) -> Result<CA, CAError> {
-        let p_info_hashmap = Extrator::paragrafos_hashmap(body);
-        if p_info_hashmap.get("n° ca").is_none() {
-            return Err(CAError::NaoEncontrado(ca));
-        }
-        let extrator = Extrator::new(ca);
+    /// Os problemas encontrados na extração são retornados no `ExtracaoRelatorio`.
+    pub async fn consultar(body: &Html, ca: u32) -> Result<(CA, ExtracaoRelatorio), CAError> {
+        CA::consultar_em(body, ca, validade::hoje()).await
+    }
 
-        let p_info_hashmap_fabricante = match extrator.secao_com_h3(body, "fabricante") {
-            Some(v) => Extrator::paragrafos_hashmap(v),
-            None => HashMap::new(),
+    /// Faz o mesmo que `consultar`, mas considera que a página foi baixada
+    /// em `hoje`. A página informa quantos dias faltam para o vencimento, e
+    /// essa contagem é conferida a partir de `hoje`; assim, o mesmo HTML
+    /// sempre gera o mesmo relatório.
+    pub async fn consultar_em(
+        body: &Html,
+        ca: u32,
+        hoje: NaiveDate,
+    ) -> Result<(CA, ExtracaoRelatorio), CAError> {
+        CA::extrair(body, ca, &Url::parse(URL_BASE).unwrap(), hoje)
+    }
+
+    /// Faz o mesmo que `consultar`, mas resolve os links relativos da página
+    /// a partir de `url_base` (o endereço de onde a página foi baixada) em
+    /// vez de `URL_BASE`. Não faz nenhuma requisição, por isso não é `async`.
+    pub fn consultar_com_base(
+        body: &Html,
+        ca: u32,
+        url_base: &Url,
+    ) -> Result<(CA, ExtracaoRelatorio), CAError> {
+        CA::extrair(body, ca, url_base, validade::hoje())
+    }
+
+    /// Interpreta a página, resolvendo os links a partir de `url_base` e
+    /// conferindo a contagem de dias da validade a partir de `hoje`. Todas as
+    /// formas de consultar passam por aqui. O `Html` não é `Send`, então quem
+    /// baixa a página dentro de um `async` (como `ClienteConsultaCA`) precisa
+    /// interpretá-la sem `await` no meio.
+    pub(crate) fn extrair(
+        body: &Html,
+        ca: u32,
+        url_base: &Url,
+        hoje: NaiveDate,
+    ) -> Result<(CA, ExtracaoRelatorio), CAError> {
+        let pagina = PaginaCA::new(body);
+        let cabecalho = match pagina.cabecalho() {
+            Some(v) if v.valor("n° ca").is_some() => v.mapa(),
+            _ => return Err(CAError::NaoEncontrado(ca)),
         };
-        let p_info_hashmap_laudo = match extrator.secao_com_h3(body, "laudos") {
-            Some(v) => Extrator::paragrafos_hashmap(v),
-            None => HashMap::new(),
+        let extrator = Extrator::new(ca)
+            .com_url_base(url_base.clone())
+            .com_hoje(hoje);
+        let complementares = pagina
+            .secao("dados complementares")
+            .map(|s| s.mapa())
+            .unwrap_or_default();
+        let referencias = extrator.referencias(&complementares);
+        let fabricante = Fabricante::extrair(&extrator, &pagina);
+        let modelos = match &referencias {
+            Some(referencias) => {
+                let marcas = modelo::marcas(&fabricante);
+                let marcas = marcas.iter().map(|m| m.as_str()).collect::<Vec<_>>();
+                modelo::modelos(referencias, &marcas)
+            }
+            None => vec![],
         };
 
-        Ok(CA {
-            validade: extrator.validade(&p_info_hashmap),
-            processo: extrator.processo(&p_info_hashmap),
+        let grupo_id = extrator.grupo_id(body);
+        let consulta = CA {
+            validade: extrator.validade(&cabecalho, body),
+            processo: extrator.processo(&cabecalho),
             descricao: extrator.descricao(body),
-            grupo: extrator.grupo(body),
-            natureza: extrator.natureza(&p_info_hashmap),
-            situacao: extrator.situacao(&p_info_hashmap),
-            aprovado_para: extrator.aprovado_para(&p_info_hashmap),
-            cores: extrator.cores(&p_info_hashmap),
-            marcacao: extrator.marcacao(&p_info_hashmap),
-            referencias: extrator.referencias(&p_info_hashmap),
-            normas: extrator.normas(body),
-            descricao_completa: extrator.descricao_completa(body),
+            grupo: extrator.grupo(body, grupo_id),
+            grupo_id,
+            natureza: extrator.natureza(&cabecalho),
+            situacao: extrator.situacao(&cabecalho),
+            aprovado_para: extrator.aprovado_para(&complementares),
+            cores: extrator.cores(&complementares),
+            marcacao: extrator.marcacao(&complementares),
+            referencias,
+            modelos,
+            observacao: extrator.observacao(&complementares),
+            normas: extrator.normas(&pagina),
+            descricao_completa: extrator.descricao_completa(&pagina),
             ca,
-            laudo: Laudo::new(ca, &p_info_hashmap_laudo),
-            fabricante: Fabricante::new(ca, &p_info_hashmap_fabricante, body),
-        })
+            laudos: extrator.laudos(&pagina),
+            fabricante,
+            historico: extrator.historico(&pagina),
+            avaliacao: extrator.avaliacao(body),
+            duvidas: extrator.duvidas(body),
+            extras: extrator.extras(&pagina),
+        };
+        Ok((consulta, extrator.relatorio()))
+    }
+
+    /// Faz o mesmo que `consultar`, mas retorna `CAError::Incompleto`
+    /// se algum campo obrigatório estiver ausente ou não puder ser
+    /// interpretado. Use esta função quando registros parciais não
+    /// forem aceitáveis.
+    pub async fn consultar_estrito(
+        body: &Html,
+        ca: u32,
+    ) -> Result<(CA, ExtracaoRelatorio), CAError> {
+        let (consulta, relatorio) = CA::consultar(body, ca).await?;
+        let campos = consulta.campos_obrigatorios_ausentes();
+        if !campos.is_empty() {
+            return Err(CAError::Incompleto(ca, campos));
+        }
+        Ok((consulta, relatorio))
+    }
+
+    /// Retorna os nomes dos campos obrigatórios que estão vazios.
+    /// Campos de laudos são nomeados com o seu índice (`laudos[0].descricao`).
+    fn campos_obrigatorios_ausentes(&self) -> Vec<String> {
+        let mut campos = vec![];
+        let mut exigir = |nome: &str, presente: bool| {
+            if !presente {
+                campos.push(nome.to_string());
+            }
+        };
+        exigir("descricao", self.descricao.is_some());
+        exigir("grupo", self.grupo.is_some());
+        exigir("natureza", self.natureza.is_some());
+        exigir("validade", self.validade.is_some());
+        exigir("situacao", self.situacao.is_some());
+        exigir("processo", self.processo.is_some());
+        exigir("aprovado_para", self.aprovado_para.is_some());
+        exigir(
+            "fabricante.razao_social",
+            self.fabricante.razao_social.is_some(),
+        );
+        exigir("fabricante.cnpj", self.fabricante.cnpj.is_some());
+        exigir("laudos", !self.laudos.is_empty());
+        for (i, laudo) in self.laudos.iter().enumerate() {
+            exigir(&format!("laudos[{i}].descricao"), laudo.descricao.is_some());
+            exigir(
+                &format!("laudos[{i}].razao_social"),
+                laudo.razao_social.is_some(),
+            );
+        }
+        campos
+    }
+
+    /// Retorna um `CABuilder` para construir um CA sem consultar o site.
+    pub fn builder(ca: u32) -> CABuilder {
+        CABuilder {
+            ca: CA {
+                ca,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Nome do equipamento (o `h1` da página).
+    pub fn descricao(&self) -> Option<&str> {
+        self.descricao.as_deref()
+    }
+    /// Grupo do EPI (`Proteção dos Membros Inferiores`, ...).
+    pub fn grupo(&self) -> Option<&GrupoEpi> {
+        self.grupo.as_ref()
+    }
+    /// Id da imagem do grupo (`grupo-epi-{id}.jpg`), guardado mesmo quando
+    /// o grupo foi identificado pela descrição.
+    pub fn grupo_id(&self) -> Option<u32> {
+        self.grupo_id
+    }
+    /// Natureza do EPI (`Nacional` ou `Importado`).
+    pub fn natureza(&self) -> Option<&Natureza> {
+        self.natureza.as_ref()
+    }
+    pub fn validade(&self) -> Option<&Validade> {
+        self.validade.as_ref()
+    }
+    pub fn descricao_completa(&self) -> Option<&str> {
+        self.descricao_completa.as_deref()
+    }
+    pub fn situacao(&self) -> Option<&Situacao> {
+        self.situacao.as_ref()
+    }
+    /// Número do processo no Ministério do Trabalho.
+    pub fn processo(&self) -> Option<u64> {
+        self.processo
+    }
+    pub fn aprovado_para(&self) -> Option<&str> {
+        self.aprovado_para.as_deref()
+    }
+    pub fn cores(&self) -> &[String] {
+        &self.cores
+    }
+    pub fn marcacao(&self) -> Option<&str> {
+        self.marcacao.as_deref()
+    }
+    pub fn referencias(&self) -> Option<&str> {
+        self.referencias.as_deref()
+    }
+    /// Códigos de modelo de `referencias`, sem a marca do fabricante.
+    /// Veja `modelo::modelos`.
+    pub fn modelos(&self) -> &[String] {
+        &self.modelos
+    }
+    /// Observação da seção "Dados Complementares" (restrições de uso, ...).
+    pub fn observacao(&self) -> Option<&str> {
+        self.observacao.as_deref()
+    }
+    pub fn normas(&self) -> &[NormaTecnica] {
+        &self.normas
+    }
+    pub fn laudos(&self) -> &[Laudo] {
+        &self.laudos
+    }
+    pub fn fabricante(&self) -> &Fabricante {
+        &self.fabricante
+    }
+    pub fn historico(&self) -> &[EventoHistorico] {
+        &self.historico
+    }
+    /// Avaliação do EPI pelos usuários do site.
+    pub fn avaliacao(&self) -> Option<&Avaliacao> {
+        self.avaliacao.as_ref()
+    }
+    /// Dúvidas registradas pelos usuários do site. Interno até o formato
+    /// da lista ser conferido numa página real (veja o módulo `duvida`).
+    #[allow(dead_code)]
+    pub(crate) fn duvidas(&self) -> &[Duvida] {
+        &self.duvidas
+    }
+    /// Pares chave/valor da página que não correspondem a nenhum campo,
+    /// agrupados pelo título da seção (`"Cabeçalho"` para o topo da página).
+    /// Assim, campos novos do site não se perdem antes de o crate os conhecer.
+    pub fn extras(&self) -> &BTreeMap<String, BTreeMap<String, String>> {
+        &self.extras
+    }
+
+    /// Diz se o CA está válido hoje (no fuso de Brasília).
+    pub fn esta_valido(&self) -> bool {
+        self.valido_em(validade::hoje())
+    }
+
+    /// Diz se o CA estava (ou estará) válido na `data`, sendo hoje a data
+    /// de Brasília. Veja `valido_em_com_hoje`.
+    pub fn valido_em(&self, data: NaiveDate) -> bool {
+        self.valido_em_com_hoje(data, validade::hoje())
+    }
+
+    /// Diz se o CA estava (ou estará) válido na `data`, sendo `hoje` o dia
+    /// em que a página foi consultada.
+    /// A resposta é dada pelo último evento do histórico anterior à `data`:
+    /// depois de um "CA Vencido" o CA não é válido; depois de um "Expedido"
+    /// ou "CA Valido" o CA é válido até o próximo evento.
+    /// Se não há evento posterior à `data`, a `validade` tem de cobrir a
+    /// `data`. A `situacao` só vale para hoje em diante: um CA suspenso
+    /// hoje pode ter sido válido ontem.
+    /// Ocorrências desconhecidas (`Ocorrencia::Outro`) são ignoradas e,
+    /// se não houver histórico, só a situação e a validade são usadas.
+    pub fn valido_em_com_hoje(&self, data: NaiveDate, hoje: NaiveDate) -> bool {
+        let eventos = self
+            .historico
+            .iter()
+            .filter(|e| !matches!(e.ocorrencia, Ocorrencia::Outro(_)))
+            .collect::<Vec<&EventoHistorico>>();
+        let vigente = self.validade.as_ref().is_some_and(|v| v.cobre(data))
+            && (data < hoje || self.situacao == Some(Situacao::Valido));
+        if eventos.is_empty() {
+            return vigente;
+        }
+        let ultimo = eventos
+            .iter()
+            .filter(|e| e.data <= data)
+            .max_by_key(|e| e.data);
+        match ultimo {
+            None => false,
+            Some(evento) => match evento.ocorrencia {
+                Ocorrencia::Vencido => false,
+                _ => eventos.iter().any(|e| e.data > data) || vigente,
+            },
+        }
     }
 }
+
+/// Constrói um `CA` sem consultar o site. Útil em testes.
+/// # Exemplo
+/// ```
+/// use cascraper::{Fabricante, Laudo, Situacao, CA};
+/// let ca = CA::builder(32551)
+///     .descricao("CALÇA")
+///     .situacao(Situacao::Valido)
+///     .laudo(Laudo::builder().razao_social("SENAI CETIQT").build())
+///     .fabricante(Fabricante::builder().nome_fantasia("FARP UNIFORMES").build())
+///     .build();
+/// assert_eq!(ca.descricao(), Some("CALÇA"));
+/// assert_eq!(ca.laudos()[0].razao_social(), Some("SENAI CETIQT"));
+/// ```
+#[derive(Debug)]
+pub struct CABuilder {
+    ca: CA,
+}
+impl CABuilder {
+    pub fn descricao(mut self, descricao: impl Into<String>) -> Self {
+        self.ca.descricao = Some(descricao.into());
+        self
+    }
+    pub fn grupo(mut self, grupo: GrupoEpi) -> Self {
+        self.ca.grupo = Some(grupo);
+        self
+    }
+    pub fn grupo_id(mut self, id: u32) -> Self {
+        self.ca.grupo_id = Some(id);
+        self
+    }
+    pub fn natureza(mut self, natureza: Natureza) -> Self {
+        self.ca.natureza = Some(natureza);
+        self
+    }
+    pub fn validade(mut self, validade: Validade) -> Self {
+        self.ca.validade = Some(validade);
+        self
+    }
+    pub fn descricao_completa(mut self, descricao_completa: impl Into<String>) -> Self {
+        self.ca.descricao_completa = Some(descricao_completa.into());
+        self
+    }
+    pub fn situacao(mut self, situacao: Situacao) -> Self {
+        self.ca.situacao = Some(situacao);
+        self
+    }
+    pub fn processo(mut self, processo: u64) -> Self {
+        self.ca.processo = Some(processo);
+        self
+    }
+    pub fn aprovado_para(mut self, aprovado_para: impl Into<String>) -> Self {
+        self.ca.aprovado_para = Some(aprovado_para.into());
+        self
+    }
+    pub fn cores(mut self, cores: Vec<String>) -> Self {
+        self.ca.cores = cores;
+        self
+    }
+    pub fn marcacao(mut self, marcacao: impl Into<String>) -> Self {
+        self.ca.marcacao = Some(marcacao.into());
+        self
+    }
+    pub fn referencias(mut self, referencias: impl Into<String>) -> Self {
+        self.ca.referencias = Some(referencias.into());
+        self
+    }
+    /// Adiciona um código de modelo.
+    pub fn modelo(mut self, modelo: impl Into<String>) -> Self {
+        self.ca.modelos.push(modelo.into());
+        self
+    }
+    pub fn observacao(mut self, observacao: impl Into<String>) -> Self {
+        self.ca.observacao = Some(observacao.into());
+        self
+    }
+    pub fn normas(mut self, normas: Vec<NormaTecnica>) -> Self {
+        self.ca.normas = normas;
+        self
+    }
+    /// Adiciona um laudo.
+    pub fn laudo(mut self, laudo: Laudo) -> Self {
+        self.ca.laudos.push(laudo);
+        self
+    }
+    pub fn fabricante(mut self, fabricante: Fabricante) -> Self {
+        self.ca.fabricante = fabricante;
+        self
+    }
+    /// Adiciona um evento ao histórico.
+    pub fn evento(mut self, evento: EventoHistorico) -> Self {
+        self.ca.historico.push(evento);
+        self
+    }
+    pub fn avaliacao(mut self, avaliacao: Avaliacao) -> Self {
+        self.ca.avaliacao = Some(avaliacao);
+        self
+    }
+    /// Adiciona uma dúvida.
+    #[allow(dead_code)]
+    pub(crate) fn duvida(mut self, duvida: Duvida) -> Self {
+        self.ca.duvidas.push(duvida);
+        self
+    }
+    /// Adiciona um par chave/valor desconhecido da `secao`.
+    pub fn extra(
+        mut self,
+        secao: impl Into<String>,
+        chave: impl Into<String>,
+        valor: impl Into<String>,
+    ) -> Self {
+        self.ca
+            .extras
+            .entry(secao.into())
+            .or_default()
+            .insert(chave.into(), valor.into());
+        self
+    }
+    pub fn build(self) -> CA {
+        self.ca
+    }
+}
+
 /// Representa um laudo.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
-struct Laudo {
-    descricao: String,
-    cnpj: u64,
-    razao_social: String,
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Laudo {
+    descricao: Option<String>,
+    cnpj: Option<Cnpj>,
+    razao_social: Option<String>,
+    numeros: Vec<String>,
+    tipo: Option<TipoAvaliacao>,
+}
+
+/// Como o EPI foi avaliado.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TipoAvaliacao {
+    /// Laudo emitido por um laboratório.
+    LaudoLaboratorio,
+    /// Certificado de conformidade do INMETRO. `ocp` é o Organismo de
+    /// Certificação de Produto que o emitiu (a razão social sem o `OCP:`).
+    CertificadoInmetro { ocp: Option<String> },
+}
+
+impl TipoAvaliacao {
+    /// Certificados têm descrição como "Certificado de Conformidade nº
+    /// BR37289007" e razão social como "OCP: Bureau Veritas Certification -
+    /// BVQI". O resto é laudo de laboratório.
+    fn de_laudo(descricao: Option<&str>, razao_social: Option<&str>) -> Option<Self> {
+        if descricao.is_none() && razao_social.is_none() {
+            return None;
+        }
+        let ocp = razao_social.and_then(|r| {
+            let (prefixo, nome) = r.split_once(':')?;
+            (prefixo.trim().eq_ignore_ascii_case("ocp")).then(|| nome.trim().to_string())
+        });
+        let certificado =
+            descricao.is_some_and(|d| normalizar(d).contains("certificado")) || ocp.is_some();
+        Some(if certificado {
+            TipoAvaliacao::CertificadoInmetro {
+                ocp: ocp.or_else(|| razao_social.map(|r| r.to_string())),
+            }
+        } else {
+            TipoAvaliacao::LaudoLaboratorio
+        })
+    }
+}
+
+/// Separa as cores do campo "Cor": `"Azul e Branca"`, `"preta/cinza"`,
+/// `"Verde; Amarela."` e `"azul, marrom"`. As cores ficam em minúsculas e
+/// sem acentos (`"Âmbar"` vira `"ambar"`), para que a busca não dependa da
+/// grafia da página.
+fn separar_cores(texto: &str) -> Vec<String> {
+    normalizar(texto)
+        .split([',', ';', '/', '\n'])
+        .flat_map(|parte| parte.split(" e "))
+        .map(|cor| {
+            cor.split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .trim_end_matches('.')
+                .to_string()
+        })
+        .filter(|cor| !cor.is_empty())
+        .collect()
+}
+
+/// Abreviação de "número" antes do número de um certificado: `nº`, `N°`
+/// ou `No.`. `\b` evita casar com o fim de palavras como "plano.".
+static ABREVIACAO_NUMERO: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\bn(?:º\.?|°\.?|o\.)\s*").unwrap());
+
+/// Separa os números de um laudo:
+/// `"85.858; 87.820; 87.821."` vira `["85.858", "87.820", "87.821"]` e
+/// `"Certificado de Conformidade nº BR37289007"` vira `["BR37289007"]`.
+fn numeros_do_laudo(descricao: &str) -> Vec<String> {
+    descricao
+        .split(';')
+        .map(|numero| {
+            let numero = numero.trim().trim_end_matches('.');
+            // "Certificado de Conformidade nº BR37289007"
+            let numero = ABREVIACAO_NUMERO
+                .find(numero)
+                .map_or(numero, |abreviacao| &numero[abreviacao.end()..]);
+            numero.trim().to_string()
+        })
+        .filter(|numero| !numero.is_empty())
+        .collect()
 }
 impl Laudo {
-    fn new(ca: u32, p_info: &HashMap<String, String>) -> Self {
-        let extrator = Extrator::new(ca);
+    pub fn builder() -> LaudoBuilder {
+        LaudoBuilder::default()
+    }
+    /// Número(s) do laudo ou do certificado.
+    pub fn descricao(&self) -> Option<&str> {
+        self.descricao.as_deref()
+    }
+    /// CNPJ do laboratório.
+    pub fn cnpj(&self) -> Option<&Cnpj> {
+        self.cnpj.as_ref()
+    }
+    /// Razão social do laboratório.
+    pub fn razao_social(&self) -> Option<&str> {
+        self.razao_social.as_deref()
+    }
+    /// Números dos laudos ou certificados da `descricao`.
+    pub fn numeros(&self) -> &[String] {
+        &self.numeros
+    }
+    /// Se é um laudo de laboratório ou um certificado do INMETRO.
+    pub fn tipo(&self) -> Option<&TipoAvaliacao> {
+        self.tipo.as_ref()
+    }
+
+    /// `indice` é a posição do laudo na página e só é usado no relatório.
+    fn extrair(extrator: &Extrator, indice: usize, p_info: &HashMap<String, String>) -> Self {
+        let prefixo = format!("laudos[{indice}]");
+        let descricao = extrator.descricao_laboratorio(&prefixo, p_info);
+        let razao_social = extrator.razao_social_laboratorio(&prefixo, p_info);
         Laudo {
-            descricao: extrator.descricao_laboratorio(p_info),
-            razao_social: extrator.razao_social_laboratorio(p_info),
-            cnpj: extrator.cnpj_laboratorio(p_info),
+            numeros: descricao
+                .as_deref()
+                .map(numeros_do_laudo)
+                .unwrap_or_default(),
+            tipo: TipoAvaliacao::de_laudo(descricao.as_deref(), razao_social.as_deref()),
+            descricao,
+            razao_social,
+            cnpj: extrator.cnpj_laboratorio(&prefixo, p_info),
         }
     }
 }
 
+/// Constrói um `Laudo`. Veja `CABuilder`.
+#[derive(Debug, Default)]
+pub struct LaudoBuilder {
+    laudo: Laudo,
+}
+impl LaudoBuilder {
+    pub fn descricao(mut self, descricao: impl Into<String>) -> Self {
+        self.laudo.descricao = Some(descricao.into());
+        self
+    }
+    pub fn cnpj(mut self, cnpj: Cnpj) -> Self {
+        self.laudo.cnpj = Some(cnpj);
+        self
+    }
+    pub fn razao_social(mut self, razao_social: impl Into<String>) -> Self {
+        self.laudo.razao_social = Some(razao_social.into());
+        self
+    }
+    /// Adiciona um número de laudo.
+    pub fn numero(mut self, numero: impl Into<String>) -> Self {
+        self.laudo.numeros.push(numero.into());
+        self
+    }
+    pub fn tipo(mut self, tipo: TipoAvaliacao) -> Self {
+        self.laudo.tipo = Some(tipo);
+        self
+    }
+    pub fn build(self) -> Laudo {
+        self.laudo
+    }
+}
+
 /// Representa um fabricante.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
-struct Fabricante {
-    razao_social: String,
-    cnpj: u64,
-    nome_fantasia: String,
-    cidade: String,
-    uf: String,
-    qtd_cas: u16,
-    link: String,
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Fabricante {
+    id: Option<u32>,
+    razao_social: Option<String>,
+    cnpj: Option<Cnpj>,
+    nome_fantasia: Option<String>,
+    cidade: Option<String>,
+    uf: Option<String>,
+    qtd_cas: Option<u16>,
+    link: Option<String>,
+    avaliacao: Option<Avaliacao>,
 }
 impl Fabricante {
-    fn new(ca: u32, p_info: &HashMap<String, String>, body: &Html) -> Self {
-        let extrator = Extrator::new(ca);
+    pub fn builder() -> FabricanteBuilder {
+        FabricanteBuilder::default()
+    }
+    /// Id do fabricante no consultaca.com (o `684` de `/fabricantes/684/...`).
+    pub fn id(&self) -> Option<u32> {
+        self.id
+    }
+    pub fn razao_social(&self) -> Option<&str> {
+        self.razao_social.as_deref()
+    }
+    pub fn cnpj(&self) -> Option<&Cnpj> {
+        self.cnpj.as_ref()
+    }
+    pub fn nome_fantasia(&self) -> Option<&str> {
+        self.nome_fantasia.as_deref()
+    }
+    pub fn cidade(&self) -> Option<&str> {
+        self.cidade.as_deref()
+    }
+    pub fn uf(&self) -> Option<&str> {
+        self.uf.as_deref()
+    }
+    /// Quantidade de CAs do fabricante.
+    pub fn qtd_cas(&self) -> Option<u16> {
+        self.qtd_cas
+    }
+    /// Link da página do fabricante no consultaca.com.
+    pub fn link(&self) -> Option<&str> {
+        self.link.as_deref()
+    }
+    /// Avaliação geral dos CAs do fabricante.
+    pub fn avaliacao(&self) -> Option<&Avaliacao> {
+        self.avaliacao.as_ref()
+    }
+
+    fn extrair(extrator: &Extrator, pagina: &PaginaCA) -> Self {
+        let secao = pagina.secao("fabricante");
+        let p_info = &secao.map(|s| s.mapa()).unwrap_or_default();
+        let body = pagina.html();
+        let (cidade, uf) = extrator.cidade_uf_extrator(p_info);
+        let (link, id) = extrator.link_e_id_fabricante(pagina);
         Fabricante {
-            cidade: extrator.cidade_fabricante(p_info),
-            uf: extrator.uf_fabricante(p_info),
+            id,
+            cidade,
+            uf,
             razao_social: extrator.razao_social_fabricante(p_info),
             cnpj: extrator.cnpj_fabricante(p_info),
             nome_fantasia: extrator.nome_fantasia_fabricante(p_info),
             qtd_cas: extrator.qtd_cas_fabricante(body),
-            link: extrator.link_fabricante(body),
+            link,
+            avaliacao: extrator.avaliacao_fabricante(body),
         }
     }
 }
 
+/// Constrói um `Fabricante`. Veja `CABuilder`.
+#[derive(Debug, Default)]
+pub struct FabricanteBuilder {
+    fabricante: Fabricante,
+}
+impl FabricanteBuilder {
+    pub fn id(mut self, id: u32) -> Self {
+        self.fabricante.id = Some(id);
+        self
+    }
+    pub fn razao_social(mut self, razao_social: impl Into<String>) -> Self {
+        self.fabricante.razao_social = Some(razao_social.into());
+        self
+    }
+    pub fn cnpj(mut self, cnpj: Cnpj) -> Self {
+        self.fabricante.cnpj = Some(cnpj);
+        self
+    }
+    pub fn nome_fantasia(mut self, nome_fantasia: impl Into<String>) -> Self {
+        self.fabricante.nome_fantasia = Some(nome_fantasia.into());
+        self
+    }
+    pub fn cidade(mut self, cidade: impl Into<String>) -> Self {
+        self.fabricante.cidade = Some(cidade.into());
+        self
+    }
+    pub fn uf(mut self, uf: impl Into<String>) -> Self {
+        self.fabricante.uf = Some(uf.into());
+        self
+    }
+    pub fn qtd_cas(mut self, qtd_cas: u16) -> Self {
+        self.fabricante.qtd_cas = Some(qtd_cas);
+        self
+    }
+    pub fn link(mut self, link: impl Into<String>) -> Self {
+        self.fabricante.link = Some(link.into());
+        self
+    }
+    pub fn avaliacao(mut self, avaliacao: Avaliacao) -> Self {
+        self.fabricante.avaliacao = Some(avaliacao);
+        self
+    }
+    pub fn build(self) -> Fabricante {
+        self.fabricante
+    }
+}
+
 /// Extrai dados da página do CA.
+/// Os problemas encontrados são acumulados no relatório.
 struct Extrator {
     ca: u32,
+    url_base: Url,
+    /// Dia em que a página foi baixada.
+    hoje: NaiveDate,
+    relatorio: RefCell<ExtracaoRelatorio>,
 }
 
 impl Extrator {
     fn new(ca: u32) -> Self {
-        Extrator { ca }
-    }
-    /// Extrai informação específica duma lista de parágrafos da classe info.
-    /// Esses parágrafos contém texto na forma chave:valor.
-    /// Exemplo de parágrafo:
-    /// ```html
-    /// <p>
-    ///     <strong>N° Processo:</strong>
-    ///     <br>
-    ///     19980274164202499
-    /// </p>
-    /// ```
-    /// Se a chave ("N° Processo", no caso acima) contiver `chave`, então esse
-    /// valor será retornado depois de passar pelo `parse_callback`.
-    /// O argumento `nome` só serve para deixar mais claro o erro que ocorre
-    /// quando não achamos a chave nos `p.info` (ou quando o valor é vazio).
-    fn paragrafos_hashmap<'a, S: Selectable<'a> + Clone>(body: S) -> HashMap<String, String> {
-        let selector = Selector::parse("p").unwrap();
-        let p_info = body.clone().select(&selector);
-        let mut resultado = HashMap::new();
-        for paragrafo in p_info {
-            let texto = paragrafo.text().collect::<String>();
-            let separator = "efa3fe20-aa7d-4672-be5a-890c505c3637";
-            let chave_separada_do_valor = texto.replacen(":", separator, 1);
-            // [chave, valor]
-            let par = chave_separada_do_valor
-                .split(separator)
-                .collect::<Vec<&str>>();
-            if par.len() == 2 && !&par[1].is_empty() {
-                resultado.insert(par[0].trim().to_lowercase(), par[1].trim().to_string());
-            }
+        Extrator {
+            ca,
+            url_base: Url::parse(URL_BASE).unwrap(),
+            hoje: validade::hoje(),
+            relatorio: RefCell::new(ExtracaoRelatorio::default()),
         }
-        resultado
     }
 
+    /// Muda o endereço usado para resolver links relativos.
+    fn com_url_base(mut self, url_base: Url) -> Self {
+        self.url_base = url_base;
+        self
+    }
+
+    /// Muda o dia usado para conferir os prazos da página.
+    fn com_hoje(mut self, hoje: NaiveDate) -> Self {
+        self.hoje = hoje;
+        self
+    }
+
+    /// Consome o extrator e retorna o relatório acumulado.
+    fn relatorio(self) -> ExtracaoRelatorio {
+        self.relatorio.into_inner()
+    }
+
+    /// Registra um problema no relatório e no log.
+    fn registrar(&self, campo: &str, tipo: TipoProblema, valor: Option<&str>) {
+        let problema = Problema {
+            campo: campo.to_string(),
+            tipo,
+            valor: valor.map(|v| v.to_string()),
+        };
+        match problema.tipo {
+            TipoProblema::Invalido(_) => error!("CA{}: {problema}", self.ca),
+            _ => warn!("CA{}: {problema}", self.ca),
+        }
+        self.relatorio.borrow_mut().problemas.push(problema);
+    }
     /// Retorna valor do `hashmap` associado à chave `informacao` depois
     /// de ter sido processado pela função `parse_callback`.
-    /// Se o hashmap não tiver a chave `informacao`, então a função
-    /// retorna o valor do argumento `padrao`.
+    /// Se o hashmap não tiver a chave `informacao` ou se o `parse_callback`
+    /// falhar, então a função retorna `None` e registra o problema
+    /// no relatório com o nome `campo`.
     /// # Exemplo
-    /// ```rust
+    /// ```rust,ignore
     /// let info = HashMap::from([("chave".to_string(), "valor".to_string())]);
-    /// assert_eq!(self.extrair("chave", &info, |a| a.to_uppercase(), "".to_string()), "VALOR");
+    /// assert_eq!(
+    ///     self.extrair("campo", "chave", &info, |a| Ok(a.to_uppercase())),
+    ///     Some("VALOR".to_string())
+    /// );
     /// ```
     fn extrair<T, F>(
         &self,
+        campo: &str,
         informacao: &str,
         hashmap: &HashMap<String, String>,
         parse_callback: F,
-        padrao: T,
-    ) -> T
+    ) -> Option<T>
     where
         F: Fn(String) -> Result<T, Box<dyn std::error::Error>>,
     {
@@ -200,450 +928,959 @@ impl Extrator {
                 Ok(value) => value,
                 Err(e) => {
                     return {
-                        error!(
-                            "CA{} {informacao}: erro no parsing. Veja: {:#?}",
-                            self.ca, e
-                        );
-                        padrao
+                        self.registrar(campo, TipoProblema::Invalido(e.to_string()), Some(value));
+                        None
                     }
                 }
             },
             None => {
                 return {
-                    warn!(
-                        "CA{}: chave '{informacao}' não está presente no hashmap.",
-                        self.ca
-                    );
-                    padrao
+                    self.registrar(campo, TipoProblema::Ausente, None);
+                    None
                 }
             }
         };
-        result
+        Some(result)
     }
 
     /// Extrai texto de dentro de um elemento do HTML da página.
     /// O fazemos partindo do primeiro elemento que corresponde ao
     /// `seletor`.
-    fn so_com_seletor(&self, body: &Html, seletor: &str) -> String {
+    fn so_com_seletor(&self, campo: &str, body: &Html, seletor: &str) -> Option<String> {
         let selector = Selector::parse(seletor).unwrap();
         let elemento_txt = match body.select(&selector).next() {
             Some(e) => e.text().collect::<String>(),
             None => {
                 return {
-                    warn!("CA{}: {seletor} não encontrado no iterator.", self.ca);
-                    "".to_string()
+                    self.registrar(campo, TipoProblema::Ausente, None);
+                    None
                 }
             }
         };
         if elemento_txt.is_empty() {
             return {
-                warn!("CA{}: {seletor} encontrado, mas está sem texto.", self.ca);
-                "".to_string()
-            };
-        }
-        elemento_txt
-    }
-    /// Retorna um elemento HTML (selecionável) com base no seu h3 interno.
-    /// # Exemplo de HTML
-    /// ```html
-    /// <div class="grupo_result_ca"> <!-- Esse é o elemento retornado -->
-    ///   <h3>Nome do h3</h3> <!-- nome do h3 (pode ser maiúsculo ou minúsculo) -->
-    ///   <p class="info">info</p>
-    /// </div>
-    /// ```
-    fn secao_com_h3<'a>(&self, body: &'a Html, nome: &str) -> Option<ElementRef<'a>> {
-        let selector = Selector::parse("h3").unwrap();
-        let h3s = body.select(&selector);
-        for h3 in h3s {
-            if h3.text().collect::<String>().to_lowercase() == nome {
-                let pai = match h3.parent() {
-                    Some(v) => v,
-                    None => {
-                        return {
-                            warn!("CA{}: pai do '{nome}' não encontrado.", self.ca);
-                            None
-                        }
-                    }
-                };
-                // ElementRef implementa o trait `Selectable`
-                return match ElementRef::wrap(pai) {
-                    Some(v) => Some(v),
-                    None => {
-                        return {
-                            warn!("CA{}: pai do '{nome}' não é um Node::Element.", self.ca);
-                            None
-                        }
-                    }
-                };
+                self.registrar(campo, TipoProblema::Vazio, None);
+                None
             };
         }
-
-        None
+        Some(elemento_txt)
     }
-
-    fn validade(&self, p_info: &HashMap<String, String>) -> NaiveDate {
-        self.extrair(
+    /// Extrai a validade e a confere com o texto de `span.validade_ca_dias`
+    /// (`vencerá daqui 699 dias`) quando ele existe.
+    fn validade(&self, p_info: &HashMap<String, String>, body: &Html) -> Option<Validade> {
+        let validade = self.extrair(
+            "validade",
             "validade",
             p_info,
-            |a| {
-                // valor na forma `26/06/2029vencerá daqui 1699 dias`
-                let validade_vec = &a[..10];
-                Ok(NaiveDate::parse_from_str(validade_vec, "%d/%m/%Y")?)
-            },
-            NaiveDate::parse_from_str("01/01/0001", "%d/%m/%Y").unwrap(),
-        )
+            // valor na forma `26/06/2029vencerá daqui 1699 dias`
+            |a| Ok(Validade::try_from(a.as_str())?),
+        );
+        let selector = Selector::parse("span.validade_ca_dias").unwrap();
+        let dias_txt = body
+            .select(&selector)
+            .next()
+            .map(|e| e.text().collect::<String>());
+        let dias = dias_txt.as_deref().and_then(validade::dias_para_vencer);
+        if let (Some(Validade::Data(data)), Some(dias)) = (&validade, dias) {
+            if !validade::validade_confere(*data, dias, self.hoje) {
+                self.registrar(
+                    "validade",
+                    TipoProblema::Divergente(format!(
+                        "data {data} não corresponde ao prazo de {dias} dias informado pela página"
+                    )),
+                    dias_txt.as_deref(),
+                );
+            }
+        }
+        validade
+    }
+    /// O grupo vem do `id` da imagem (veja `grupo_id`) e da descrição
+    /// `.grupo-epi-desc`. Veja `GrupoEpi::new`.
+    fn grupo(&self, body: &Html, id: Option<u32>) -> Option<GrupoEpi> {
+        let descricao = self.so_com_seletor("grupo", body, ".grupo-epi-desc");
+        GrupoEpi::new(id, descricao.as_deref())
     }
-    fn grupo(&self, body: &Html) -> String {
-        self.so_com_seletor(body, ".grupo-epi-desc")
+    /// Id da imagem `img.grupo-epi-img`.
+    fn grupo_id(&self, body: &Html) -> Option<u32> {
+        let selector = Selector::parse("img.grupo-epi-img").unwrap();
+        body.select(&selector)
+            .next()
+            .and_then(|img| img.attr("src"))
+            .and_then(GrupoEpi::id_da_imagem)
     }
-    fn descricao(&self, body: &Html) -> String {
-        self.so_com_seletor(body, "h1")
+    fn descricao(&self, body: &Html) -> Option<String> {
+        self.so_com_seletor("descricao", body, "h1")
     }
-    fn normas(&self, body: &Html) -> Vec<String> {
-        let selector = Selector::parse(".lista-normas").unwrap();
-        let normas = match body.select(&selector).next() {
-            Some(e) => e.text().map(|x| x.to_string()).collect::<Vec<String>>(),
+    /// Itens da lista da seção "Normas".
+    fn normas(&self, pagina: &PaginaCA) -> Vec<NormaTecnica> {
+        let lista = pagina
+            .secao("normas")
+            .and_then(|secao| secao.listas().first());
+        let normas = match lista {
+            Some(v) => v
+                .iter()
+                .map(|norma| NormaTecnica::from(norma.as_str()))
+                .collect::<Vec<_>>(),
             None => {
                 return {
-                    warn!("CA{}: norma não encontrada no iterator.", self.ca);
+                    self.registrar("normas", TipoProblema::Ausente, None);
                     vec![]
                 }
             }
         };
         if normas.is_empty() {
             return {
-                warn!("CA{}: normas encontradas, mas não têm conteúdo.", self.ca);
+                self.registrar("normas", TipoProblema::Vazio, None);
                 vec![]
             };
         }
         normas
     }
-    fn processo(&self, p_info: &HashMap<String, String>) -> u64 {
-        self.extrair("n° processo", p_info, |a| Ok(a.trim().parse::<u64>()?), 0)
+    fn processo(&self, p_info: &HashMap<String, String>) -> Option<u64> {
+        self.extrair("processo", "n° processo", p_info, |a| {
+            Ok(a.trim().parse::<u64>()?)
+        })
     }
-    fn natureza(&self, p_info: &HashMap<String, String>) -> String {
-        self.extrair("natureza", p_info, Ok, "".to_string())
+    fn natureza(&self, p_info: &HashMap<String, String>) -> Option<Natureza> {
+        self.extrair("natureza", "natureza", p_info, |a| {
+            Ok(Natureza::from(a.as_str()))
+        })
     }
-    fn situacao(&self, p_info: &HashMap<String, String>) -> String {
-        self.extrair("situação", p_info, Ok, "".to_string())
+    fn situacao(&self, p_info: &HashMap<String, String>) -> Option<Situacao> {
+        self.extrair("situacao", "situação", p_info, |a| {
+            Ok(Situacao::from(a.as_str()))
+        })
     }
     fn cores(&self, p_info: &HashMap<String, String>) -> Vec<String> {
-        self.extrair(
-            "cor",
-            p_info,
-            |a| {
-                let cores_vec = a
-                    .split(", ")
-                    .map(|x| x.trim().to_lowercase().replace(".", ""))
-                    .collect::<Vec<String>>();
-                Ok(cores_vec)
-            },
-            vec![],
-        )
+        self.extrair("cores", "cor", p_info, |a| Ok(separar_cores(&a)))
+            .unwrap_or_default()
+    }
+    fn marcacao(&self, p_info: &HashMap<String, String>) -> Option<String> {
+        self.extrair("marcacao", "marcação", p_info, Ok)
     }
-    fn marcacao(&self, p_info: &HashMap<String, String>) -> String {
-        self.extrair("marcação", p_info, Ok, "".to_string())
+    fn referencias(&self, p_info: &HashMap<String, String>) -> Option<String> {
+        self.extrair("referencias", "referências", p_info, Ok)
     }
-    fn referencias(&self, p_info: &HashMap<String, String>) -> String {
-        self.extrair("referências", p_info, Ok, "".to_string())
+    /// A observação é opcional: a sua falta não é registrada no relatório.
+    fn observacao(&self, p_info: &HashMap<String, String>) -> Option<String> {
+        p_info.get("observação").cloned()
     }
-    fn aprovado_para(&self, p_info: &HashMap<String, String>) -> String {
-        self.extrair("aprovado para", p_info, Ok, "".to_string())
+    fn aprovado_para(&self, p_info: &HashMap<String, String>) -> Option<String> {
+        self.extrair("aprovado_para", "aprovado para", p_info, Ok)
     }
-    fn descricao_completa(&self, body: &Html) -> String {
-        let nome_h3 = "descrição completa";
-        let p_selector = Selector::parse("p").unwrap();
-        let elemento_descr = match self.secao_com_h3(body, nome_h3) {
+    /// Todos os parágrafos da seção "Descrição Completa", separados por uma
+    /// linha em branco. Os `<br>` dentro de um parágrafo viram quebras de
+    /// linha. Sem parágrafos, usamos o texto solto da seção.
+    fn descricao_completa(&self, pagina: &PaginaCA) -> Option<String> {
+        let descricao = pagina
+            .secao("descrição completa")
+            .map(|secao| match secao.paragrafos() {
+                [] => secao.texto(),
+                paragrafos => paragrafos.join("\n\n"),
+            });
+        match descricao.filter(|d| !d.is_empty()) {
+            Some(d) => Some(d),
+            None => {
+                self.registrar("descricao_completa", TipoProblema::Ausente, None);
+                None
+            }
+        }
+    }
+    /// Retorna todos os laudos da seção "Laudos".
+    /// Cada laudo é um grupo de parágrafos e os grupos são separados
+    /// por um `div.divider`:
+    /// ```html
+    /// <div class="grupo_result_ca">
+    ///   <h3>Laudos</h3>
+    ///   <p class="info"><strong>N° do Laudo:</strong><br />...</p>
+    ///   <p class="info"><strong>CNPJ do Laboratório:</strong><br />...</p>
+    ///   <p class="info"><strong>Razão Social:</strong><br />...</p>
+    ///   <div class="divider"></div>
+    ///   <p class="info"><strong>N° do Laudo:</strong><br />...</p>
+    ///   ...
+    /// </div>
+    /// ```
+    fn laudos(&self, pagina: &PaginaCA) -> Vec<Laudo> {
+        let secao = match pagina.secao("laudos") {
             Some(v) => v,
-            None => return "".to_string(),
+            None => {
+                return {
+                    self.registrar("laudos", TipoProblema::Ausente, None);
+                    vec![]
+                }
+            }
         };
-        let p = match elemento_descr.select(&p_selector).next() {
+        secao
+            .blocos()
+            .iter()
+            .enumerate()
+            .map(|(i, bloco)| Laudo::extrair(self, i, &pagina_ca::mapa(bloco.iter())))
+            .collect()
+    }
+    /// Extrai os eventos da tabela da seção "Histórico":
+    /// ```html
+    /// <div class="grupo_result_ca">
+    ///   <h3>Histórico</h3>
+    ///   <table class="tabela-interna">
+    ///     <tr><th>Data da Alteração (Ordem Crescente)</th><th>Ocorrência</th></tr>
+    ///     <tr><td>21/03/2013</td><td>Expedido</td></tr>
+    ///   </table>
+    /// </div>
+    /// ```
+    /// Linhas com data inválida são descartadas.
+    fn historico(&self, pagina: &PaginaCA) -> Vec<EventoHistorico> {
+        let tabela = match pagina
+            .secao("histórico")
+            .and_then(|secao| secao.tabelas().first())
+        {
             Some(v) => v,
-            None => return "".to_string(),
+            None => {
+                return {
+                    self.registrar("historico", TipoProblema::Ausente, None);
+                    vec![]
+                }
+            }
         };
-        p.text().collect::<String>()
+        let mut historico = vec![];
+        for tds in &tabela.linhas {
+            if tds.len() != 2 {
+                self.registrar(
+                    "historico",
+                    TipoProblema::Invalido("a linha não é um par (data, ocorrência)".to_string()),
+                    Some(&tds.join(" | ")),
+                );
+                continue;
+            }
+            match NaiveDate::parse_from_str(&tds[0], "%d/%m/%Y") {
+                Ok(data) => historico.push(EventoHistorico {
+                    data,
+                    ocorrencia: Ocorrencia::from(tds[1].as_str()),
+                }),
+                Err(e) => self.registrar(
+                    "historico",
+                    TipoProblema::Invalido(e.to_string()),
+                    Some(&tds[0]),
+                ),
+            }
+        }
+        historico
+    }
+    /// Pares de todas as seções cujas chaves não estão em `CHAVES_CONHECIDAS`.
+    /// Se a chave se repete na seção (em dois laudos, por exemplo), os
+    /// valores são unidos com `"; "`.
+    fn extras(&self, pagina: &PaginaCA) -> BTreeMap<String, BTreeMap<String, String>> {
+        let mut extras: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+        for secao in pagina.cabecalho().into_iter().chain(pagina.secoes()) {
+            let titulo = normalizar(secao.titulo());
+            let conhecidas = CHAVES_CONHECIDAS
+                .iter()
+                .find(|(t, _)| normalizar(t) == titulo)
+                .map_or(&[][..], |(_, chaves)| *chaves);
+            for (chave, valor) in secao.pares() {
+                if conhecidas
+                    .iter()
+                    .any(|c| normalizar(c) == normalizar(chave))
+                {
+                    continue;
+                }
+                let nome_secao = match secao.titulo() {
+                    "" => EXTRAS_CABECALHO,
+                    t => t,
+                };
+                extras
+                    .entry(nome_secao.to_string())
+                    .or_default()
+                    .entry(chave.clone())
+                    .and_modify(|v| {
+                        v.push_str("; ");
+                        v.push_str(valor);
+                    })
+                    .or_insert_with(|| valor.clone());
+            }
+        }
+        extras
+    }
+    /// Avaliação do EPI (`div#box_avaliacao`). Veja `Avaliacao::do_bloco`.
+    fn avaliacao(&self, body: &Html) -> Option<Avaliacao> {
+        self.avaliacao_do_bloco("avaliacao", body, "#box_avaliacao")
+    }
+    /// Avaliação geral do fabricante (`div.box-avaliacao-fabricante`).
+    fn avaliacao_fabricante(&self, body: &Html) -> Option<Avaliacao> {
+        self.avaliacao_do_bloco("fabricante.avaliacao", body, "div.box-avaliacao-fabricante")
+    }
+    fn avaliacao_do_bloco(&self, campo: &str, body: &Html, seletor: &str) -> Option<Avaliacao> {
+        let selector = Selector::parse(seletor).unwrap();
+        let bloco = match body.select(&selector).next() {
+            Some(v) => v,
+            None => {
+                self.registrar(campo, TipoProblema::Ausente, None);
+                return None;
+            }
+        };
+        match Avaliacao::do_bloco(bloco) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                self.registrar(
+                    campo,
+                    TipoProblema::Invalido(e),
+                    Some(bloco.text().collect::<String>().trim()),
+                );
+                None
+            }
+        }
+    }
+    /// Dúvidas de `div#lista_comentarios`. Veja `Duvida::da_lista`.
+    fn duvidas(&self, body: &Html) -> Vec<Duvida> {
+        let selector = Selector::parse("#lista_comentarios").unwrap();
+        match body.select(&selector).next() {
+            Some(lista) => Duvida::da_lista(lista).unwrap_or_else(|e| {
+                let valor = lista.text().collect::<String>();
+                self.registrar("duvidas", TipoProblema::Invalido(e), Some(valor.trim()));
+                vec![]
+            }),
+            None => {
+                self.registrar("duvidas", TipoProblema::Ausente, None);
+                vec![]
+            }
+        }
     }
-    fn descricao_laboratorio(&self, p_info: &HashMap<String, String>) -> String {
-        self.extrair("n° do laudo", p_info, Ok, "".to_string())
+    fn descricao_laboratorio(
+        &self,
+        prefixo: &str,
+        p_info: &HashMap<String, String>,
+    ) -> Option<String> {
+        self.extrair(&format!("{prefixo}.descricao"), "n° do laudo", p_info, Ok)
     }
-    fn razao_social_laboratorio(&self, p_info: &HashMap<String, String>) -> String {
-        self.extrair("razão social", p_info, Ok, "".to_string())
+    fn razao_social_laboratorio(
+        &self,
+        prefixo: &str,
+        p_info: &HashMap<String, String>,
+    ) -> Option<String> {
+        self.extrair(
+            &format!("{prefixo}.razao_social"),
+            "razão social",
+            p_info,
+            Ok,
+        )
     }
 
-    fn cnpj_laboratorio(&self, p_info: &HashMap<String, String>) -> u64 {
-        self.extrair("cnpj do laboratório", p_info, extrair_numeros, 0)
+    fn cnpj_laboratorio(&self, prefixo: &str, p_info: &HashMap<String, String>) -> Option<Cnpj> {
+        self.extrair(
+            &format!("{prefixo}.cnpj"),
+            "cnpj do laboratório",
+            p_info,
+            |a| Ok(a.parse()?),
+        )
     }
-    fn razao_social_fabricante(&self, p_info: &HashMap<String, String>) -> String {
-        self.extrair("razão social", p_info, Ok, "".to_string())
+    fn razao_social_fabricante(&self, p_info: &HashMap<String, String>) -> Option<String> {
+        self.extrair("fabricante.razao_social", "razão social", p_info, Ok)
     }
-    fn cnpj_fabricante(&self, p_info: &HashMap<String, String>) -> u64 {
-        self.extrair("cnpj", p_info, extrair_numeros, 0)
+    fn cnpj_fabricante(&self, p_info: &HashMap<String, String>) -> Option<Cnpj> {
+        self.extrair("fabricante.cnpj", "cnpj", p_info, |a| Ok(a.parse()?))
     }
-    fn nome_fantasia_fabricante(&self, p_info: &HashMap<String, String>) -> String {
-        self.extrair("nome fantasia", p_info, Ok, "".to_string())
+    fn nome_fantasia_fabricante(&self, p_info: &HashMap<String, String>) -> Option<String> {
+        self.extrair("fabricante.nome_fantasia", "nome fantasia", p_info, Ok)
     }
     /// Retorna um par na forma (cidade, UF).
-    fn cidade_uf_extrator(&self, p_info: &HashMap<String, String>) -> (String, String) {
-        let padrao = "".to_string();
-        let cidade_uf_str = self.extrair("cidade/uf", p_info, Ok, "".to_string());
-        if cidade_uf_str.is_empty() {
-            warn!("CA{}: par cidade/uf não encontrado (fabricante).", self.ca);
-            return (padrao.to_owned(), padrao);
-        }
+    fn cidade_uf_extrator(
+        &self,
+        p_info: &HashMap<String, String>,
+    ) -> (Option<String>, Option<String>) {
+        let cidade_uf_str = match self.extrair("fabricante.cidade_uf", "cidade/uf", p_info, Ok) {
+            Some(v) => v,
+            None => return (None, None),
+        };
         let cidade_uf_vec = cidade_uf_str.split("/").collect::<Vec<&str>>();
         // se par existe, então ...
         if cidade_uf_vec.len() == 2 {
-            (cidade_uf_vec[0].to_string(), cidade_uf_vec[1].to_string())
+            (
+                Some(cidade_uf_vec[0].to_string()),
+                Some(cidade_uf_vec[1].to_string()),
+            )
         } else {
-            warn!("CA{}: par cidade/uf não é um par (fabricante).", self.ca);
-            (padrao.to_owned(), padrao)
+            self.registrar(
+                "fabricante.cidade_uf",
+                TipoProblema::Invalido("não é um par cidade/UF".to_string()),
+                Some(&cidade_uf_str),
+            );
+            (None, None)
         }
     }
-    fn cidade_fabricante(&self, p_info: &HashMap<String, String>) -> String {
-        self.cidade_uf_extrator(p_info).0
-    }
-    fn uf_fabricante(&self, p_info: &HashMap<String, String>) -> String {
-        self.cidade_uf_extrator(p_info).1
-    }
-    fn qtd_cas_fabricante(&self, body: &Html) -> u16 {
-        let padrao = 0;
+    fn qtd_cas_fabricante(&self, body: &Html) -> Option<u16> {
+        let padrao = None;
         let selector = Selector::parse(".total.info.load-blockui").unwrap();
         let result = match body.select(&selector).next() {
-            Some(e) => match e.text().collect::<String>().parse::<u16>() {
-                Ok(v) => v,
-                Err(_) => {
-                    return {
-                        warn!(
-                            "CA{}: quantidade de CA's do fabricante não é um inteiro.",
-                            self.ca
-                        );
-                        padrao
+            Some(e) => {
+                let texto = e.text().collect::<String>();
+                match texto.parse::<u16>() {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        return {
+                            self.registrar(
+                                "fabricante.qtd_cas",
+                                TipoProblema::Invalido(e.to_string()),
+                                Some(&texto),
+                            );
+                            padrao
+                        }
                     }
                 }
-            },
+            }
             None => {
                 return {
-                    warn!(
-                        "CA{}: quantidade de CA's do fabricante não encontrada.",
-                        self.ca
-                    );
+                    self.registrar("fabricante.qtd_cas", TipoProblema::Ausente, None);
                     padrao
                 }
             }
         };
         result
     }
-    fn link_fabricante(&self, body: &Html) -> String {
-        let padrao = "".to_string();
-        let selector = Selector::parse("[href*=\"https://consultaca.com/fabricantes/\"]").unwrap();
-        let selected_iter = body.select(&selector);
-        let a_element = match selected_iter.into_iter().next() {
+    /// Retorna o link (absoluto) da página do fabricante e o id do fabricante.
+    /// Preferimos o link da seção "Fabricante", que é relativo:
+    /// ```html
+    /// <a href="/fabricantes/684/farp-industria-de-roupas-ltda"
+    ///    data-lb="|pj-684|ca-32551|tp-2|CALÇA">FARP INDUSTRIA DE ROUPAS LTDA</a>
+    /// ```
+    /// O id é o número que segue `/fabricantes/` ou, na falta dele,
+    /// o número de `pj-` no atributo `data-lb`.
+    fn link_e_id_fabricante(&self, pagina: &PaginaCA) -> (Option<String>, Option<u32>) {
+        let selector = Selector::parse("a[href*=\"/fabricantes/\"]").unwrap();
+        let a_element = pagina
+            .secao("fabricante")
+            .and_then(|secao| secao.elemento().select(&selector).next())
+            .or_else(|| pagina.html().select(&selector).next());
+        let a_element = match a_element {
             Some(v) => v,
             None => {
                 return {
-                    warn!("CA{}: link do fabricante não encontrado.", self.ca);
-                    padrao
+                    self.registrar("fabricante.link", TipoProblema::Ausente, None);
+                    (None, None)
                 }
             }
         };
-        match a_element.attr("href") {
-            Some(v) => v.to_string(),
+        let href = match a_element.attr("href") {
+            Some(v) => v,
             None => {
-                warn!(
-                    "CA{}: elemento 'a' encontrado, mas não contém link (fabricante).",
-                    self.ca
-                );
-                padrao
+                return {
+                    self.registrar("fabricante.link", TipoProblema::Vazio, None);
+                    (None, None)
+                }
             }
+        };
+        let link = match self.url_base.join(href) {
+            Ok(v) => v,
+            Err(e) => {
+                return {
+                    self.registrar(
+                        "fabricante.link",
+                        TipoProblema::Invalido(e.to_string()),
+                        Some(href),
+                    );
+                    (None, None)
+                }
+            }
+        };
+        let id_do_link = link
+            .path_segments()
+            .and_then(|mut partes| {
+                partes.find(|parte| *parte == "fabricantes")?;
+                partes.next()
+            })
+            .and_then(|parte| parte.parse::<u32>().ok());
+        let id_do_data_lb = || {
+            a_element
+                .attr("data-lb")?
+                .split('|')
+                .find_map(|parte| parte.strip_prefix("pj-"))?
+                .parse::<u32>()
+                .ok()
+        };
+        let id = id_do_link.or_else(id_do_data_lb);
+        if id.is_none() {
+            self.registrar(
+                "fabricante.id",
+                TipoProblema::Invalido("o link não contém o id do fabricante".to_string()),
+                Some(href),
+            );
         }
+        (Some(link.to_string()), id)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::pagina::SUCESSO;
-    use log::LevelFilter;
-    use log4rs::{
-        append::{console::ConsoleAppender, file::FileAppender},
-        config::{Appender, Root},
-        encode::pattern::PatternEncoder,
-        Config,
-    };
-    use std::{
-        fs::{self},
-        sync::{LazyLock, Mutex},
-    };
-
-    static HANDLE: LazyLock<Mutex<log4rs::Handle>> = LazyLock::new(|| Mutex::new(setup_log()));
-
-    /// Retorna um `Handle` que será usado para mudar
-    /// as configurações do logger padrão.
-    #[allow(unused_must_use)]
-    fn setup_log() -> log4rs::Handle {
-        let default = ConsoleAppender::builder()
-            .encoder(Box::new(PatternEncoder::new("{d} - {m}{n}")))
-            .build();
-
-        let config = Config::builder()
-            .appender(Appender::builder().build("default", Box::new(default)))
-            .build(Root::builder().appender("default").build(LevelFilter::Warn))
-            .unwrap();
-
-        log4rs::init_config(config).unwrap()
-    }
-
-    /// Cria uma configuração para o logger e retorna um id.
-    /// O logger padrão passará a escrever no arquivo `/tmp/{test_id}.log`.
-    /// Cada teste que usa logging deve chamar essa função.
-    /// Essa função não é o suficiente para isolar os logs de cada teste.
-    /// Nós temos de rodar cada teste num processo separado para que o handle
-    /// não seja alterado quando não deve ser alterado.
-    /// (veja [`esse comentário`](https://github.com/rust-lang/rust/issues/47506#issuecomment-1655503393)).
-    fn config_specific_test(test_id: &str) -> String {
-        let encoder_str = "{d} - {m}{n}";
-        let requests = FileAppender::builder()
-            .append(false)
-            .encoder(Box::new(PatternEncoder::new(encoder_str)))
-            .build(format!("/tmp/{test_id}.log"))
-            .unwrap();
-
-        let config = Config::builder()
-            .appender(Appender::builder().build("requests", Box::new(requests)))
-            .build(
-                Root::builder()
-                    .appender("requests")
-                    .build(LevelFilter::Warn),
-            )
-            .unwrap();
-        HANDLE.lock().unwrap().set_config(config);
-        test_id.to_string()
-    }
-
-    /// Lê o conteúdo do log de um teste (veja `config_specific_test`).
-    fn read_test(test_id: String) -> String {
-        fs::read_to_string(format!("/tmp/{test_id}.log")).unwrap()
-    }
 
     use super::*;
     #[test]
     fn extrair() {
         let info = HashMap::from([("chave".to_string(), "valor".to_string())]);
         assert_eq!(
-            Extrator::new(777).extrair("chave", &info, Ok, "".to_string()),
-            "valor"
+            Extrator::new(777).extrair("campo", "chave", &info, Ok),
+            Some("valor".to_string())
         );
     }
 
     #[test]
     fn extrair_sem_key_no_hashmap() {
-        let test_id = config_specific_test("extrair_sem_key_no_hashmap");
         let info = HashMap::from([]);
+        let extrator = Extrator::new(777);
+        assert_eq!(
+            extrator.extrair("campo", "SoliDeoGloria", &info, Ok),
+            None::<String>
+        );
         assert_eq!(
-            Extrator::new(777).extrair("SoliDeoGloria", &info, Ok, "".to_string()),
-            ""
+            extrator.relatorio().problemas,
+            vec![Problema {
+                campo: "campo".to_string(),
+                tipo: TipoProblema::Ausente,
+                valor: None,
+            }]
         );
-        let logs = read_test(test_id);
-        println!("{}", logs);
-        assert!(logs.contains("CA777: chave 'SoliDeoGloria'"));
     }
 
     #[test]
     fn extrair_parsing() {
         let info = HashMap::from([("chave".to_string(), "valor".to_string())]);
         assert_eq!(
-            Extrator::new(777).extrair("chave", &info, |a| Ok(a.to_uppercase()), "".to_string()),
-            "VALOR"
+            Extrator::new(777).extrair("campo", "chave", &info, |a| Ok(a.to_uppercase())),
+            Some("VALOR".to_string())
         );
     }
 
-    #[test]
-    fn paragrafos_hashmap() {
-        let html = r#"
-    <!DOCTYPE html>
-    <meta charset="utf-8">
-     <p>
-         <strong>N° Processo:</strong>
-         <br>
-         19980274164202499
-     </p>
-     <p><strong>Situação:</strong><br><span style="color: rgb(255, 0, 0); font-weight: bold; --darkreader-inline-color: #ff1a1a;" data-darkreader-inline-color="">VENCIDO</span></p>
-"#;
-
-        let documento = Html::parse_document(html);
-        let resultado = Extrator::paragrafos_hashmap(&documento);
-        assert_eq!(
-            resultado,
-            HashMap::from([
-                ("n° processo".to_string(), "19980274164202499".to_string()),
-                ("situação".to_string(), "VENCIDO".to_string())
-            ])
-        );
-    }
     #[test]
     fn erro_no_parsing() {
-        let test_id = config_specific_test("erro_no_parsing");
         let info = HashMap::from([("chave".to_string(), "valor".to_string())]);
-        Extrator::new(777).extrair(
-            "chave",
-            &info,
-            |_| Err("IesusHominumSalvator".into()),
-            "".to_string(),
+        let extrator = Extrator::new(777);
+        assert_eq!(
+            extrator.extrair("campo", "chave", &info, |_| Err::<String, _>(
+                "IesusHominumSalvator".into()
+            )),
+            None
+        );
+        assert_eq!(
+            extrator.relatorio().problemas,
+            vec![Problema {
+                campo: "campo".to_string(),
+                tipo: TipoProblema::Invalido("IesusHominumSalvator".to_string()),
+                valor: Some("valor".to_string()),
+            }]
         );
-        let content = read_test(test_id);
-        println!("{}", content);
-        assert!(content.contains("CA777 chave: erro no parsing"));
     }
     #[tokio::test]
     async fn consultar() {
-        let test_id = config_specific_test("consultar_com_erro");
         let body = Html::parse_document(SUCESSO);
-        let ca = match CA::consultar(&body, 32551).await {
+        // a página de exemplo foi salva em 08/11/2024 ("vencerá daqui 699
+        // dias"); a divergência do prazo é testada em
+        // `validade_conferida_a_partir_de_hoje`
+        let hoje = NaiveDate::from_ymd_opt(2024, 11, 8).unwrap();
+        let (ca, relatorio) = match CA::consultar_em(&body, 32551, hoje).await {
             Ok(v) => v,
             Err(e) => panic!("erro na consulta: {:#?}", e),
         };
         let ca_esperado = CA {
-    descricao: "CALÇA".to_string(),
-    grupo: "Proteção dos Membros Inferiores".to_string(),
-    natureza: "Nacional".to_string(),
-    validade: NaiveDate::from_ymd_opt(2026,10, 8).unwrap(),
-    descricao_completa: "Calça de segurança confeccionada em uma camada de tecido Uniforte Pro FR, composto por 100% de algodão, fabricado pela empresa Companhia de Tecidos Santanense, com gramatura nominal de 7,66 oz/yd² (260 g/m²), ATPV 9,6 cal/cm².".to_string(),
-    situacao: "VÁLIDO".to_string(),
-    processo: 19980216122202352,
-    aprovado_para: "PROTEÇÃO DAS PERNAS DO USUÁRIO CONTRA AGENTES TÉRMICOS PROVENIENTES DE ARCO ELÉTRICO E FOGO REPENTINO.".to_string(),
+    descricao: Some("CALÇA".to_string()),
+    grupo: Some(GrupoEpi::MembrosInferiores),
+    grupo_id: Some(5),
+    natureza: Some(Natureza::Nacional),
+    validade: Some(Validade::Data(NaiveDate::from_ymd_opt(2026,10, 8).unwrap())),
+    descricao_completa: Some("Calça de segurança confeccionada em uma camada de tecido Uniforte Pro FR, composto por 100% de algodão, fabricado pela empresa Companhia de Tecidos Santanense, com gramatura nominal de 7,66 oz/yd² (260 g/m²), ATPV 9,6 cal/cm².".to_string()),
+    situacao: Some(Situacao::Valido),
+    processo: Some(19980216122202352),
+    aprovado_para: Some("PROTEÇÃO DAS PERNAS DO USUÁRIO CONTRA AGENTES TÉRMICOS PROVENIENTES DE ARCO ELÉTRICO E FOGO REPENTINO.".to_string()),
     cores: vec![],
-    marcacao: "Na etiqueta".to_string(),
-    referencias: "F23.16".to_string(),
+    marcacao: Some("Na etiqueta".to_string()),
+    referencias: Some("F23.16".to_string()),
+    modelos: vec!["F23.16".to_string()],
+    observacao: Some("A seleção e o uso deste equipamento devem ser precedidos de análise de risco da atividade que considere demais equipamentos necessários para proteção completa do usuário.".to_string()),
     normas: vec![
-        "ASTM D 6413:2015".to_string(),
-        "ASTM F 1506-10a".to_string(),
-        "ASTM F 1930:2018".to_string(),
-        "ASTM F1959/F1959M-14".to_string(),
-        "ASTM F2621-19".to_string(),
+        NormaTecnica::from("ASTM D 6413:2015"),
+        NormaTecnica::from("ASTM F 1506-10a"),
+        NormaTecnica::from("ASTM F 1930:2018"),
+        NormaTecnica::from("ASTM F1959/F1959M-14"),
+        NormaTecnica::from("ASTM F2621-19"),
     ],
     ca: 32551,
-    laudo: Laudo {
-        descricao: "85.858; 87.820; 87.821.".to_string(),
-        cnpj: 63025530004282,
-        razao_social: "SEÇÃO TÉCNICA DE DESENVOLVIMENTO TECNOLÓGICO EM SAÚDE - IEE/USP".to_string(),
-    },
+    laudos: vec![
+        Laudo {
+            descricao: Some("1092-23-1/2; 1639-23-1/2; 1640-23-1/2; 3156-21; 3172-21; 3180-21; 3187-21; 3196-21; 3204-21.".to_string()),
+            cnpj: Some("03851105000142".parse().unwrap()),
+            razao_social: Some("SENAI CETIQT".to_string()),
+            numeros: ["1092-23-1/2", "1639-23-1/2", "1640-23-1/2", "3156-21", "3172-21", "3180-21", "3187-21", "3196-21", "3204-21"]
+                .map(|n| n.to_string())
+                .to_vec(),
+            tipo: Some(TipoAvaliacao::LaudoLaboratorio),
+        },
+        Laudo {
+            descricao: Some("85.858; 87.820; 87.821.".to_string()),
+            cnpj: Some("63025530004282".parse().unwrap()),
+            razao_social: Some("SEÇÃO TÉCNICA DE DESENVOLVIMENTO TECNOLÓGICO EM SAÚDE - IEE/USP".to_string()),
+            numeros: vec!["85.858".to_string(), "87.820".to_string(), "87.821".to_string()],
+            tipo: Some(TipoAvaliacao::LaudoLaboratorio),
+        },
+    ],
     fabricante: Fabricante {
-        razao_social: "FARP INDUSTRIA DE ROUPAS LTDA".to_string(),
-        cnpj: 177445000141,
-        nome_fantasia: "FARP UNIFORMES".to_string(),
-        cidade: "ITUMBIARA".to_string(),
-        uf: "GO".to_string(),
-        qtd_cas: 28,
-        link: "".to_string(),
-    }
+        razao_social: Some("FARP INDUSTRIA DE ROUPAS LTDA".to_string()),
+        cnpj: Some("00177445000141".parse().unwrap()),
+        nome_fantasia: Some("FARP UNIFORMES".to_string()),
+        cidade: Some("ITUMBIARA".to_string()),
+        uf: Some("GO".to_string()),
+        qtd_cas: Some(28),
+        link: Some("https://consultaca.com/fabricantes/684/farp-industria-de-roupas-ltda".to_string()),
+        id: Some(684),
+        avaliacao: Some(Avaliacao::SemAvaliacoes),
+    },
+    historico: vec![
+        EventoHistorico { data: NaiveDate::from_ymd_opt(2013, 3, 21).unwrap(), ocorrencia: Ocorrencia::Expedido },
+        EventoHistorico { data: NaiveDate::from_ymd_opt(2015, 1, 23).unwrap(), ocorrencia: Ocorrencia::Expedido },
+        EventoHistorico { data: NaiveDate::from_ymd_opt(2017, 12, 18).unwrap(), ocorrencia: Ocorrencia::Vencido },
+        EventoHistorico { data: NaiveDate::from_ymd_opt(2018, 4, 26).unwrap(), ocorrencia: Ocorrencia::Expedido },
+        EventoHistorico { data: NaiveDate::from_ymd_opt(2019, 6, 13).unwrap(), ocorrencia: Ocorrencia::Expedido },
+        EventoHistorico { data: NaiveDate::from_ymd_opt(2023, 1, 25).unwrap(), ocorrencia: Ocorrencia::Vencido },
+        EventoHistorico { data: NaiveDate::from_ymd_opt(2023, 3, 2).unwrap(), ocorrencia: Ocorrencia::Valido },
+        EventoHistorico { data: NaiveDate::from_ymd_opt(2023, 10, 31).unwrap(), ocorrencia: Ocorrencia::Vencido },
+        EventoHistorico { data: NaiveDate::from_ymd_opt(2023, 12, 4).unwrap(), ocorrencia: Ocorrencia::Expedido },
+    ],
+    avaliacao: Some(Avaliacao::SemAvaliacoes),
+    duvidas: vec![],
+    extras: BTreeMap::new(),
 };
         assert_eq!(ca, ca_esperado);
 
-        let content = read_test(test_id);
+        let campos = relatorio
+            .problemas
+            .iter()
+            .map(|p| p.campo.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(campos, vec!["cores"]);
+        assert_eq!(relatorio.problemas[0].tipo, TipoProblema::Ausente);
+    }
+    #[tokio::test]
+    async fn valido_em() {
+        let body = Html::parse_document(SUCESSO);
+        let (ca, _) = CA::consultar(&body, 32551).await.unwrap();
+        let data = |a, m, d| NaiveDate::from_ymd_opt(a, m, d).unwrap();
+        // antes do primeiro "Expedido"
+        assert!(!ca.valido_em(data(2013, 3, 20)));
+        assert!(ca.valido_em(data(2013, 3, 21)));
+        assert!(ca.valido_em(data(2016, 1, 1)));
+        // entre "CA Vencido" e "Expedido"
+        assert!(!ca.valido_em(data(2018, 1, 1)));
+        assert!(ca.valido_em(data(2023, 5, 1)));
+        assert!(!ca.valido_em(data(2023, 11, 15)));
+        // depois do último evento vale a validade atual
+        assert!(ca.valido_em(data(2026, 10, 8)));
+        assert!(!ca.valido_em(data(2026, 10, 9)));
+    }
+    #[tokio::test]
+    async fn validade_conferida_a_partir_de_hoje() {
+        let body = Html::parse_document(SUCESSO);
+        // a página foi salva em 08/11/2024: "vencerá daqui 699 dias"
+        let salva_em = NaiveDate::from_ymd_opt(2024, 11, 8).unwrap();
+        let (_, relatorio) = CA::consultar_em(&body, 32551, salva_em).await.unwrap();
+        assert_eq!(relatorio.do_campo("validade").count(), 0);
+        let depois = NaiveDate::from_ymd_opt(2025, 11, 8).unwrap();
+        for _ in 0..2 {
+            let (_, relatorio) = CA::consultar_em(&body, 32551, depois).await.unwrap();
+            let problemas = relatorio.do_campo("validade").collect::<Vec<_>>();
+            assert_eq!(problemas.len(), 1);
+            assert!(matches!(problemas[0].tipo, TipoProblema::Divergente(_)));
+        }
+    }
+    #[test]
+    fn situacao_atual_so_vale_de_hoje_em_diante() {
+        let data = |a, m, d| NaiveDate::from_ymd_opt(a, m, d).unwrap();
+        let hoje = data(2025, 6, 1);
+        let suspenso = CA::builder(1)
+            .situacao(Situacao::Suspenso)
+            .validade(Validade::Data(data(2027, 1, 1)))
+            .evento(EventoHistorico {
+                data: data(2022, 1, 1),
+                ocorrencia: Ocorrencia::Expedido,
+            })
+            .build();
+        // válido depois do último evento, antes de ser suspenso
+        assert!(suspenso.valido_em_com_hoje(data(2024, 5, 1), hoje));
+        assert!(!suspenso.valido_em_com_hoje(hoje, hoje));
+        assert!(!suspenso.valido_em_com_hoje(data(2026, 1, 1), hoje));
+        assert!(!suspenso.valido_em_com_hoje(data(2021, 1, 1), hoje));
+
+        let sem_historico = CA::builder(2)
+            .situacao(Situacao::Cancelado)
+            .validade(Validade::Data(data(2027, 1, 1)))
+            .build();
+        assert!(sem_historico.valido_em_com_hoje(data(2025, 5, 31), hoje));
+        assert!(!sem_historico.valido_em_com_hoje(hoje, hoje));
+
+        let valido = CA::builder(3)
+            .situacao(Situacao::Valido)
+            .validade(Validade::Data(data(2027, 1, 1)))
+            .build();
+        assert!(valido.valido_em_com_hoje(data(2026, 12, 31), hoje));
+        assert!(!valido.valido_em_com_hoje(data(2027, 1, 2), hoje));
+    }
+    #[tokio::test]
+    async fn consultar_estrito() {
+        let body = Html::parse_document(SUCESSO);
+        assert!(CA::consultar_estrito(&body, 32551).await.is_ok());
+
+        let incompleto = SUCESSO
+            .replace(
+                "<p><strong>N° Processo:</strong><br />19980216122202352</p>",
+                "",
+            )
+            .replace("00.177.445/0001-41", "00.177.445/0001-42");
+        let body = Html::parse_document(&incompleto);
         assert_eq!(
-            content.matches("encontrad").count() + content.matches("presente").count(),
-            2
+            CA::consultar_estrito(&body, 32551).await.unwrap_err(),
+            CAError::Incompleto(
+                32551,
+                vec!["processo".to_string(), "fabricante.cnpj".to_string()]
+            )
         );
     }
+    #[test]
+    fn link_fabricante_com_base() {
+        let body = Html::parse_document(SUCESSO);
+        let base = Url::parse("http://localhost:8080/espelho/").unwrap();
+        let (ca, _) = CA::consultar_com_base(&body, 32551, &base).unwrap();
+        assert_eq!(
+            ca.fabricante().link(),
+            Some("http://localhost:8080/fabricantes/684/farp-industria-de-roupas-ltda")
+        );
+
+        // link do selo, sem o id no caminho
+        let html = r#"<div class="grupo_result_ca"><h3>Fabricante</h3>
+            <a href="/fabricantes/selo/17/3m-do-brasil-ltda-3m" data-lb="|pj-17|ca-445|tp-2|RESPIRADOR">3M</a>
+        </div>"#;
+        let extrator = Extrator::new(445);
+        let documento = Html::parse_document(html);
+        let (link, id) = extrator.link_e_id_fabricante(&PaginaCA::new(&documento));
+        assert_eq!(
+            link.as_deref(),
+            Some("https://consultaca.com/fabricantes/selo/17/3m-do-brasil-ltda-3m")
+        );
+        assert_eq!(id, Some(17));
+        assert!(extrator.relatorio().esta_vazio());
+    }
+    #[test]
+    fn duvidas_em_formato_desconhecido() {
+        let html = r#"<div id="lista_comentarios"><ul><li>Lavável?</li></ul></div>"#;
+        let extrator = Extrator::new(445);
+        assert_eq!(extrator.duvidas(&Html::parse_document(html)), vec![]);
+        let problemas = extrator.relatorio().problemas;
+        assert_eq!(problemas.len(), 1);
+        assert_eq!(problemas[0].campo, "duvidas");
+        assert!(matches!(problemas[0].tipo, TipoProblema::Invalido(_)));
+        assert_eq!(problemas[0].valor.as_deref(), Some("Lavável?"));
+
+        // as dúvidas ficam fora da serialização
+        let ca = CA::builder(445)
+            .duvida(Duvida {
+                autor: None,
+                data: None,
+                texto: "Lavável?".to_string(),
+                respostas: vec![],
+            })
+            .build();
+        assert_eq!(ca.duvidas().len(), 1);
+        assert!(!serde_json::to_string(&ca).unwrap().contains("duvidas"));
+    }
+    #[test]
+    fn builder() {
+        let data = NaiveDate::from_ymd_opt(2026, 10, 8).unwrap();
+        let cnpj: Cnpj = "00.177.445/0001-41".parse().unwrap();
+        let ca = CA::builder(32551)
+            .descricao("CALÇA")
+            .situacao(Situacao::Valido)
+            .validade(Validade::Data(data))
+            .processo(19980216122202352)
+            .fabricante(Fabricante::builder().cnpj(cnpj.clone()).qtd_cas(28).build())
+            .build();
+        assert_eq!(ca.ca, 32551);
+        assert_eq!(ca.descricao(), Some("CALÇA"));
+        assert_eq!(ca.validade(), Some(&Validade::Data(data)));
+        assert_eq!(ca.processo(), Some(19980216122202352));
+        assert_eq!(ca.fabricante().cnpj(), Some(&cnpj));
+        assert_eq!(ca.fabricante().qtd_cas(), Some(28));
+        assert_eq!(ca.grupo(), None);
+        assert!(ca.laudos().is_empty());
+        // sem histórico, valem a situação e a validade
+        assert!(ca.valido_em(data));
+    }
+    #[test]
+    fn cores() {
+        assert_eq!(separar_cores("Azul e Branca"), vec!["azul", "branca"]);
+        assert_eq!(separar_cores("preta/cinza"), vec!["preta", "cinza"]);
+        assert_eq!(
+            separar_cores("Verde; AMARELA, Âmbar."),
+            vec!["verde", "amarela", "ambar"]
+        );
+        assert_eq!(
+            separar_cores("azul-marinho,  cinza claro e laranja."),
+            vec!["azul-marinho", "cinza claro", "laranja"]
+        );
+        assert_eq!(separar_cores("."), Vec::<String>::new());
+    }
+    #[tokio::test]
+    async fn descricao_completa_com_paragrafos() {
+        let html = SUCESSO.replace(
+            "ATPV 9,6 cal/cm².</p>",
+            "ATPV 9,6 cal/cm².<br />Tamanhos: P ao GG.</p><p class='info'>Lavar à mão.</p>",
+        );
+        let (ca, _) = CA::consultar(&Html::parse_document(&html), 32551)
+            .await
+            .unwrap();
+        let descricao = ca.descricao_completa().unwrap();
+        assert!(descricao.ends_with("ATPV 9,6 cal/cm².\nTamanhos: P ao GG.\n\nLavar à mão."));
+    }
+    /// Sobe um servidor local que responde `resposta` a cada conexão e
+    /// retorna a URL base dele.
+    async fn servidor(resposta: &'static str) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endereco = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0; 1024];
+                let _ = socket.read(&mut buf).await;
+                let _ = socket.write_all(resposta.as_bytes()).await;
+            }
+        });
+        format!("http://{endereco}/")
+    }
+    #[tokio::test]
+    async fn pagina_com_erros_de_rede() {
+        let client = Client::new();
+        let nenhuma = PoliticaRetentativa::nenhuma();
+        let url = servidor("HTTP/1.1 200 OK\r\ncontent-length: 13\r\n\r\n<p>oi</p>\r\n\r\n").await;
+        let corpo = corpo_da_pagina(&client, &url, 1, &nenhuma, None)
+            .await
+            .unwrap();
+        assert_eq!(corpo, ("<p>oi</p>\r\n\r\n".to_string(), 1));
+
+        let url = servidor("HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\n\r\n").await;
+        assert_eq!(
+            corpo_da_pagina(&client, &url, 2, &nenhuma, None)
+                .await
+                .unwrap_err(),
+            CAError::StatusHttp(2, 503)
+        );
+
+        // corpo menor do que o content-length
+        let url = servidor("HTTP/1.1 200 OK\r\ncontent-length: 100\r\n\r\n<p>").await;
+        assert!(matches!(
+            corpo_da_pagina(&client, &url, 3, &nenhuma, None)
+                .await
+                .unwrap_err(),
+            CAError::Corpo(3, _)
+        ));
+
+        // ninguém escutando na porta
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        drop(listener);
+        assert!(matches!(
+            corpo_da_pagina(&client, &url, 4, &nenhuma, None)
+                .await
+                .unwrap_err(),
+            CAError::Conexao(4, _)
+        ));
+
+        // servidor que aceita a conexão e nunca responde
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let _conexao = listener.accept().await;
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        });
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_millis(200))
+            .build()
+            .unwrap();
+        assert_eq!(
+            corpo_da_pagina(&client, &url, 5, &nenhuma, None)
+                .await
+                .unwrap_err(),
+            CAError::TempoEsgotado(5)
+        );
+    }
+    #[test]
+    fn certificado_inmetro() {
+        assert_eq!(
+            numeros_do_laudo("Certificado de Conformidade nº BR37289007"),
+            vec!["BR37289007"]
+        );
+        assert_eq!(
+            numeros_do_laudo("Certificado de Conformidade N° 123; 456."),
+            vec!["123", "456"]
+        );
+        assert_eq!(
+            numeros_do_laudo("Certificado No. BR-123; Nº. 456"),
+            vec!["BR-123", "456"]
+        );
+        // caracteres que mudam de tamanho em minúsculas não quebram o corte
+        assert_eq!(numeros_do_laudo("İSTANBUL TEST nº 42"), vec!["42"]);
+        assert_eq!(numeros_do_laudo("İİİ; Ünal 7"), vec!["İİİ", "Ünal 7"]);
+        // "no." dentro de palavras não é a abreviação de número
+        assert_eq!(
+            numeros_do_laudo("Ensaio conforme plano. 12; Laudo do ano. 2020"),
+            vec!["Ensaio conforme plano. 12", "Laudo do ano. 2020"]
+        );
+        assert_eq!(numeros_do_laudo("CASINO. 5"), vec!["CASINO. 5"]);
+        assert_eq!(
+            TipoAvaliacao::de_laudo(
+                Some("Certificado de Conformidade nº BR37289007"),
+                Some("OCP: Bureau Veritas Certification - BVQI"),
+            ),
+            Some(TipoAvaliacao::CertificadoInmetro {
+                ocp: Some("Bureau Veritas Certification - BVQI".to_string())
+            })
+        );
+        assert_eq!(
+            TipoAvaliacao::de_laudo(Some("CERTIFICADO nº 1"), Some("UL DO BRASIL")),
+            Some(TipoAvaliacao::CertificadoInmetro {
+                ocp: Some("UL DO BRASIL".to_string())
+            })
+        );
+        assert_eq!(
+            TipoAvaliacao::de_laudo(Some("85.858; 87.820."), Some("IEE/USP")),
+            Some(TipoAvaliacao::LaudoLaboratorio)
+        );
+        assert_eq!(TipoAvaliacao::de_laudo(None, None), None);
+    }
+    #[tokio::test]
+    async fn extras() {
+        let html = SUCESSO
+            .replace(
+                "<p><strong>Natureza:</strong>",
+                "<p><strong>Tipo de Proteção:</strong><br />Térmica</p><p><strong>Natureza:</strong>",
+            )
+            .replace(
+                "<h3>Dados Complementares</h3>",
+                "<h3>Dados Complementares</h3><p class=\"info\"><strong>Tamanhos:</strong><br />P ao GG</p>",
+            )
+            .replace(
+                "<h3>Laudos</h3>",
+                "<h3>Laudos</h3><p class=\"info\"><strong>Data do Laudo:</strong><br />01/02/2023</p>",
+            );
+        let body = Html::parse_document(&html);
+        let (ca, _) = CA::consultar(&body, 32551).await.unwrap();
+        let esperado = CA::builder(32551)
+            .extra("Cabeçalho", "Tipo de Proteção", "Térmica")
+            .extra("Dados Complementares", "Tamanhos", "P ao GG")
+            .extra("Laudos", "Data do Laudo", "01/02/2023")
+            .build();
+        assert_eq!(ca.extras(), esperado.extras());
+        // os campos conhecidos continuam nos seus lugares
+        assert_eq!(ca.natureza(), Some(&Natureza::Nacional));
+        assert_eq!(ca.laudos().len(), 2);
+
+        let (ca, _) = CA::consultar(&Html::parse_document(SUCESSO), 32551)
+            .await
+            .unwrap();
+        assert!(ca.extras().is_empty());
+    }
     #[tokio::test]
     async fn ca_nao_encontrado() {
         let body = Html::parse_document("");
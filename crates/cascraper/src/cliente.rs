@@ -0,0 +1,501 @@
+/*!
+Cliente HTTP reutilizável para consultar CAs no site do ConsultaCA.
+*/
+use crate::ca::{corpo_da_pagina, URL_BASE};
+use crate::errors::{CAError, ClienteErro};
+use crate::limite::{Limitador, LimiteRequisicoes};
+use crate::relatorio::ExtracaoRelatorio;
+use crate::retentativa::PoliticaRetentativa;
+use crate::validade;
+use crate::CA;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use reqwest::{Client, Proxy, Url};
+use scraper::Html;
+use std::time::Duration;
+
+/// User-Agent enviado quando nenhum outro é configurado.
+pub const USER_AGENT_PADRAO: &str = concat!("cascraper/", env!("CARGO_PKG_VERSION"));
+
+/// Resultado de `ClienteConsultaCA::consultar_com_relatorio`.
+pub type ResultadoConsulta = Result<(CA, ExtracaoRelatorio), CAError>;
+
+/// Cliente que baixa e interpreta páginas de CA.
+/// Guarda um `reqwest::Client`, então deve ser criado uma vez e reutilizado
+/// (clonar é barato e as clonagens compartilham as conexões).
+/// # Exemplo
+/// ```no_run
+/// use cascraper::{ClienteConsultaCA, LimiteRequisicoes};
+/// use std::time::Duration;
+/// # async fn exemplo() -> Result<(), Box<dyn std::error::Error>> {
+/// let cliente = ClienteConsultaCA::builder()
+///     .url_base("http://localhost:8080/")
+///     .timeout_conexao(Duration::from_secs(5))
+///     .timeout_leitura(Duration::from_secs(20))
+///     .user_agent("minha-empresa/1.0")
+///     .limite(LimiteRequisicoes::new(2.0, 5))
+///     .build()?;
+/// let ca = cliente.consultar(32551).await?;
+/// println!("{:?}", ca.descricao());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClienteConsultaCA {
+    client: Client,
+    url_base: Url,
+    retentativas: PoliticaRetentativa,
+    limitador: Option<Limitador>,
+}
+
+impl ClienteConsultaCA {
+    /// Cliente com a configuração padrão, apontando para `URL_BASE`.
+    pub fn new() -> Self {
+        ClienteConsultaCA::builder()
+            .build()
+            .expect("a configuração padrão do cliente é válida")
+    }
+
+    /// Retorna um `ClienteConsultaCABuilder` para configurar o cliente.
+    pub fn builder() -> ClienteConsultaCABuilder {
+        ClienteConsultaCABuilder::default()
+    }
+
+    /// Endereço de onde as páginas são baixadas.
+    pub fn url_base(&self) -> &Url {
+        &self.url_base
+    }
+
+    /// Limite de requisições por segundo, se houver.
+    pub fn limite(&self) -> Option<LimiteRequisicoes> {
+        self.limitador.as_ref().map(|l| l.limite())
+    }
+
+    /// Política usada para repetir as requisições que falham.
+    pub fn retentativas(&self) -> &PoliticaRetentativa {
+        &self.retentativas
+    }
+
+    /// Pega o HTML da página do CA.
+    pub async fn pagina(&self, ca: u32) -> Result<Html, CAError> {
+        let (corpo, _) = self.corpo(ca).await?;
+        Ok(Html::parse_document(&corpo))
+    }
+
+    /// Baixa e interpreta a página do CA.
+    pub async fn consultar(&self, ca: u32) -> Result<CA, CAError> {
+        self.consultar_com_relatorio(ca)
+            .await
+            .map(|(consulta, _)| consulta)
+    }
+
+    /// Faz o mesmo que `consultar`, mas retorna também os problemas
+    /// encontrados na extração e quantas requisições foram feitas.
+    pub async fn consultar_com_relatorio(
+        &self,
+        ca: u32,
+    ) -> Result<(CA, ExtracaoRelatorio), CAError> {
+        let (corpo, tentativas) = self.corpo(ca).await?;
+        let hoje = validade::hoje();
+        let (consulta, mut relatorio) =
+            CA::extrair(&Html::parse_document(&corpo), ca, &self.url_base, hoje)?;
+        relatorio.tentativas = tentativas;
+        Ok((consulta, relatorio))
+    }
+
+    /// Consulta vários CAs ao mesmo tempo, com no máximo `concorrencia`
+    /// consultas em andamento, e devolve os resultados conforme a `ordem`.
+    /// Todas as consultas usam este cliente (e, portanto, o mesmo limite
+    /// de requisições e as mesmas conexões). Um CA que falha não
+    /// interrompe os outros.
+    /// ```no_run
+    /// use cascraper::{ClienteConsultaCA, Ordem};
+    /// use futures_util::StreamExt;
+    /// # async fn exemplo() {
+    /// let cliente = ClienteConsultaCA::new();
+    /// let mut resultados = cliente.consultar_lote([32551, 445, 9999], 4, Ordem::Entrada);
+    /// while let Some((ca, resultado)) = resultados.next().await {
+    ///     match resultado {
+    ///         Ok(consulta) => println!("{ca}: {:?}", consulta.descricao()),
+    ///         Err(e) => eprintln!("{e}"),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn consultar_lote<I>(
+        &self,
+        cas: I,
+        concorrencia: usize,
+        ordem: Ordem,
+    ) -> BoxStream<'_, (u32, Result<CA, CAError>)>
+    where
+        I: IntoIterator<Item = u32>,
+        I::IntoIter: Send + 'static,
+    {
+        self.consultar_lote_com_relatorio(cas, concorrencia, ordem)
+            .map(|(ca, resultado)| (ca, resultado.map(|(consulta, _)| consulta)))
+            .boxed()
+    }
+
+    /// Faz o mesmo que `consultar_lote`, mas retorna também os problemas
+    /// encontrados na extração de cada CA.
+    pub fn consultar_lote_com_relatorio<I>(
+        &self,
+        cas: I,
+        concorrencia: usize,
+        ordem: Ordem,
+    ) -> BoxStream<'_, (u32, ResultadoConsulta)>
+    where
+        I: IntoIterator<Item = u32>,
+        I::IntoIter: Send + 'static,
+    {
+        let consultas = stream::iter(cas)
+            .map(move |ca| async move { (ca, self.consultar_com_relatorio(ca).await) });
+        // `buffered(0)` nunca começaria nenhuma consulta
+        let concorrencia = concorrencia.max(1);
+        match ordem {
+            Ordem::Entrada => consultas.buffered(concorrencia).boxed(),
+            Ordem::Conclusao => consultas.buffer_unordered(concorrencia).boxed(),
+        }
+    }
+
+    async fn corpo(&self, ca: u32) -> Result<(String, u32), CAError> {
+        corpo_da_pagina(
+            &self.client,
+            self.url_base.as_str(),
+            ca,
+            &self.retentativas,
+            self.limitador.as_ref(),
+        )
+        .await
+    }
+}
+
+impl Default for ClienteConsultaCA {
+    fn default() -> Self {
+        ClienteConsultaCA::new()
+    }
+}
+
+/// Ordem dos resultados de `ClienteConsultaCA::consultar_lote`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Ordem {
+    /// A mesma ordem dos CAs de entrada. Um CA lento segura os seguintes.
+    #[default]
+    Entrada,
+    /// Cada resultado sai assim que fica pronto.
+    Conclusao,
+}
+
+/// Configura um `ClienteConsultaCA`. Nenhuma opção é obrigatória.
+#[derive(Debug, Clone)]
+pub struct ClienteConsultaCABuilder {
+    url_base: String,
+    timeout_conexao: Option<Duration>,
+    timeout_leitura: Option<Duration>,
+    user_agent: String,
+    proxy: Option<String>,
+    conexoes_por_host: Option<usize>,
+    retentativas: PoliticaRetentativa,
+    limite: Option<LimiteRequisicoes>,
+}
+
+impl Default for ClienteConsultaCABuilder {
+    fn default() -> Self {
+        ClienteConsultaCABuilder {
+            url_base: URL_BASE.to_string(),
+            timeout_conexao: None,
+            timeout_leitura: None,
+            user_agent: USER_AGENT_PADRAO.to_string(),
+            proxy: None,
+            conexoes_por_host: None,
+            retentativas: PoliticaRetentativa::default(),
+            limite: Some(LimiteRequisicoes::default()),
+        }
+    }
+}
+
+impl ClienteConsultaCABuilder {
+    /// Endereço do site (um espelho ou um servidor de testes).
+    /// O padrão é `URL_BASE`.
+    pub fn url_base(mut self, url_base: impl Into<String>) -> Self {
+        self.url_base = url_base.into();
+        self
+    }
+    /// Tempo máximo para estabelecer a conexão.
+    pub fn timeout_conexao(mut self, timeout: Duration) -> Self {
+        self.timeout_conexao = Some(timeout);
+        self
+    }
+    /// Tempo máximo sem receber dados depois de conectado.
+    pub fn timeout_leitura(mut self, timeout: Duration) -> Self {
+        self.timeout_leitura = Some(timeout);
+        self
+    }
+    /// O padrão é `USER_AGENT_PADRAO`.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+    /// Proxy HTTP usado em todas as requisições (`"http://proxy:3128"`).
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+    /// Máximo de conexões ociosas mantidas abertas por host.
+    pub fn conexoes_por_host(mut self, conexoes: usize) -> Self {
+        self.conexoes_por_host = Some(conexoes);
+        self
+    }
+    /// Quando e quantas vezes repetir uma requisição que falhou.
+    /// O padrão é `PoliticaRetentativa::default()`.
+    pub fn retentativas(mut self, politica: PoliticaRetentativa) -> Self {
+        self.retentativas = politica;
+        self
+    }
+    /// Limita as requisições feitas pelo cliente e por todas as suas
+    /// cópias (`clone`). O padrão é `LimiteRequisicoes::default()`.
+    pub fn limite(mut self, limite: LimiteRequisicoes) -> Self {
+        self.limite = Some(limite);
+        self
+    }
+    /// Desliga o limite de requisições. Use só com um servidor próprio
+    /// (um espelho ou um servidor de testes), nunca com o site.
+    pub fn sem_limite(mut self) -> Self {
+        self.limite = None;
+        self
+    }
+
+    pub fn build(self) -> Result<ClienteConsultaCA, ClienteErro> {
+        let mut url_base = self.url_base;
+        if !url_base.ends_with('/') {
+            url_base.push('/');
+        }
+        let url_base =
+            Url::parse(&url_base).map_err(|e| ClienteErro::UrlInvalida(url_base, e.to_string()))?;
+        if let Some(limite) = self.limite.filter(|l| !l.e_valido()) {
+            return Err(ClienteErro::LimiteInvalido(format!(
+                "{} por segundo com rajada de {}",
+                limite.por_segundo, limite.rajada
+            )));
+        }
+        let mut builder = Client::builder().user_agent(self.user_agent);
+        if let Some(timeout) = self.timeout_conexao {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.timeout_leitura {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(proxy) = self.proxy {
+            let proxy = Proxy::all(&proxy).map_err(|e| ClienteErro::Proxy(proxy, e.to_string()))?;
+            builder = builder.proxy(proxy);
+        }
+        if let Some(conexoes) = self.conexoes_por_host {
+            builder = builder.pool_max_idle_per_host(conexoes);
+        }
+        let client = builder
+            .build()
+            .map_err(|e| ClienteErro::Construcao(e.to_string()))?;
+        Ok(ClienteConsultaCA {
+            client,
+            url_base,
+            retentativas: self.retentativas,
+            limitador: self.limite.map(Limitador::new),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pagina::SUCESSO;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Sobe um servidor local que responde 503 às primeiras `falhas`
+    /// requisições, serve a página de `SUCESSO` nas seguintes e guarda as
+    /// requisições recebidas. O CA 0 recebe uma página sem CA.
+    async fn servidor(falhas: usize) -> (String, tokio::sync::mpsc::UnboundedReceiver<String>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endereco = listener.local_addr().unwrap();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut atendidas = 0;
+            while let Ok((mut socket, _)) = listener.accept().await {
+                atendidas += 1;
+                let mut buf = vec![0; 4096];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let requisicao = String::from_utf8_lossy(&buf[..n]).to_string();
+                let pagina = if requisicao.starts_with("GET /espelho/0 ") {
+                    "<html><body></body></html>"
+                } else {
+                    SUCESSO
+                };
+                let _ = tx.send(requisicao);
+                let resposta = if atendidas <= falhas {
+                    "HTTP/1.1 503 Service Unavailable\r\nretry-after: 0\r\ncontent-length: 0\r\nconnection: close\r\n\r\n".to_string()
+                } else {
+                    let cabecalho = "HTTP/1.1 200 OK\r\ncontent-type: text/html; charset=utf-8\r\nconnection: close";
+                    format!(
+                        "{cabecalho}\r\ncontent-length: {}\r\n\r\n{pagina}",
+                        pagina.len()
+                    )
+                };
+                let _ = socket.write_all(resposta.as_bytes()).await;
+            }
+        });
+        (format!("http://{endereco}/espelho"), rx)
+    }
+
+    #[tokio::test]
+    async fn consultar_em_servidor_local() {
+        let (url, mut requisicoes) = servidor(0).await;
+        let cliente = ClienteConsultaCA::builder()
+            .url_base(url)
+            .user_agent("teste/1.0")
+            .timeout_conexao(Duration::from_secs(5))
+            .timeout_leitura(Duration::from_secs(5))
+            .conexoes_por_host(2)
+            .build()
+            .unwrap();
+        let ca = cliente.consultar(32551).await.unwrap();
+        assert_eq!(ca.ca, 32551);
+        assert_eq!(ca.descricao(), Some("CALÇA"));
+
+        let requisicao = requisicoes.recv().await.unwrap().to_lowercase();
+        assert!(requisicao.starts_with("get /espelho/32551 http/1.1"));
+        assert!(requisicao.contains("user-agent: teste/1.0"));
+        // os links relativos da página são resolvidos a partir do espelho
+        let fabricantes = cliente.url_base().join("/fabricantes/").unwrap();
+        assert!(ca
+            .fabricante()
+            .link()
+            .is_some_and(|l| l.starts_with(fabricantes.as_str())));
+    }
+
+    #[tokio::test]
+    async fn repete_quando_o_site_falha() {
+        let politica = PoliticaRetentativa {
+            tentativas: 3,
+            espera_inicial: Duration::from_millis(10),
+            espera_maxima: Duration::from_millis(100),
+            ..Default::default()
+        };
+        let (url, mut requisicoes) = servidor(2).await;
+        let cliente = ClienteConsultaCA::builder()
+            .url_base(url)
+            .retentativas(politica.clone())
+            .build()
+            .unwrap();
+        let (ca, relatorio) = cliente.consultar_com_relatorio(32551).await.unwrap();
+        assert_eq!(ca.ca, 32551);
+        assert_eq!(relatorio.tentativas, 3);
+        for _ in 0..3 {
+            requisicoes.recv().await.unwrap();
+        }
+
+        let (url, _requisicoes) = servidor(3).await;
+        let cliente = ClienteConsultaCA::builder()
+            .url_base(url)
+            .retentativas(politica)
+            .build()
+            .unwrap();
+        assert_eq!(
+            cliente.consultar(1).await.unwrap_err(),
+            CAError::StatusHttp(1, 503)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limite_compartilhado_entre_copias() {
+        let (url, _requisicoes) = servidor(0).await;
+        let cliente = ClienteConsultaCA::builder()
+            .url_base(url)
+            .limite(LimiteRequisicoes::new(20.0, 1))
+            .build()
+            .unwrap();
+        let inicio = tokio::time::Instant::now();
+        let tarefas = (0..4)
+            .map(|i| {
+                let cliente = cliente.clone();
+                tokio::spawn(async move { cliente.consultar(32551 + i).await })
+            })
+            .collect::<Vec<_>>();
+        for tarefa in tarefas {
+            tarefa.await.unwrap().unwrap();
+        }
+        // a primeira sai na hora; as outras três, a cada 50ms. O relógio
+        // pausado também avança enquanto o runtime espera pelos sockets, então
+        // só o limite de baixo é exato.
+        let decorrido = inicio.elapsed();
+        assert!(decorrido >= Duration::from_millis(150), "{decorrido:?}");
+        assert!(decorrido < Duration::from_millis(250), "{decorrido:?}");
+    }
+
+    #[tokio::test]
+    async fn lote() {
+        let (url, _requisicoes) = servidor(0).await;
+        // servidor local: o limite só deixaria o teste lento
+        let cliente = ClienteConsultaCA::builder()
+            .url_base(url)
+            .sem_limite()
+            .build()
+            .unwrap();
+        let cas = vec![32551, 0, 445, 12, 7];
+
+        let resultados = cliente
+            .consultar_lote(cas.clone(), 3, Ordem::Entrada)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(
+            resultados.iter().map(|(ca, _)| *ca).collect::<Vec<_>>(),
+            cas
+        );
+        for (ca, resultado) in &resultados {
+            match ca {
+                0 => assert_eq!(resultado, &Err(CAError::NaoEncontrado(0))),
+                _ => assert_eq!(resultado.as_ref().unwrap().ca, *ca),
+            }
+        }
+
+        let mut concluidos = cliente
+            .consultar_lote(cas.clone(), 0, Ordem::Conclusao)
+            .map(|(ca, _)| ca)
+            .collect::<Vec<_>>()
+            .await;
+        concluidos.sort_unstable();
+        assert_eq!(concluidos, vec![0, 7, 12, 445, 32551]);
+    }
+
+    #[test]
+    fn configuracao_invalida() {
+        assert!(matches!(
+            ClienteConsultaCA::builder().url_base("não é url").build(),
+            Err(ClienteErro::UrlInvalida(..))
+        ));
+        assert!(matches!(
+            ClienteConsultaCA::builder().proxy("::::").build(),
+            Err(ClienteErro::Proxy(..))
+        ));
+        assert!(matches!(
+            ClienteConsultaCA::builder()
+                .limite(LimiteRequisicoes::new(0.0, 5))
+                .build(),
+            Err(ClienteErro::LimiteInvalido(_))
+        ));
+        assert_eq!(
+            ClienteConsultaCA::new().url_base().as_str(),
+            "https://consultaca.com/"
+        );
+        assert_eq!(
+            ClienteConsultaCA::new().limite(),
+            Some(LimiteRequisicoes::default())
+        );
+        assert_eq!(
+            ClienteConsultaCA::builder()
+                .sem_limite()
+                .build()
+                .unwrap()
+                .limite(),
+            None
+        );
+    }
+}
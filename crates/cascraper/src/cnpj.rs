@@ -0,0 +1,165 @@
+/*!
+CNPJ com validação dos dígitos verificadores.
+Já aceita o CNPJ alfanumérico: os 12 primeiros caracteres podem ser
+letras maiúsculas ou dígitos, e os 2 últimos (dígitos verificadores)
+são sempre numéricos.
+*/
+use crate::errors::CnpjErro;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Um CNPJ válido. É serializado na forma `00.177.445/0001-41`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Cnpj(String);
+
+impl Cnpj {
+    /// Retorna os 14 caracteres do CNPJ sem pontuação (`00177445000141`).
+    pub fn digitos(&self) -> &str {
+        &self.0
+    }
+
+    /// Retorna o CNPJ na forma `00.177.445/0001-41`.
+    pub fn formatado(&self) -> String {
+        let c = &self.0;
+        format!(
+            "{}.{}.{}/{}-{}",
+            &c[..2],
+            &c[2..5],
+            &c[5..8],
+            &c[8..12],
+            &c[12..]
+        )
+    }
+
+    /// Diz se o CNPJ usa o formato alfanumérico (tem alguma letra).
+    pub fn alfanumerico(&self) -> bool {
+        self.0.chars().any(|c| c.is_ascii_alphabetic())
+    }
+}
+
+/// Calcula um dígito verificador (módulo 11).
+/// O valor de cada caractere é o seu código ASCII menos 48, o que dá
+/// 0..=9 para dígitos e 17..=42 para letras.
+fn digito_verificador(caracteres: &[u8]) -> u8 {
+    let soma: u32 = caracteres
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, c)| (*c as u32 - b'0' as u32) * (i as u32 % 8 + 2))
+        .sum();
+    match soma % 11 {
+        0 | 1 => 0,
+        resto => (11 - resto) as u8,
+    }
+}
+
+impl FromStr for Cnpj {
+    type Err = CnpjErro;
+    /// Aceita o CNPJ com ou sem pontuação.
+    fn from_str(texto: &str) -> Result<Self, Self::Err> {
+        let cnpj = texto
+            .chars()
+            .filter(|c| !matches!(c, '.' | '/' | '-') && !c.is_whitespace())
+            .map(|c| c.to_ascii_uppercase())
+            .collect::<String>();
+        if cnpj.chars().count() != 14 {
+            return Err(CnpjErro::Tamanho(texto.to_string()));
+        }
+        for (i, c) in cnpj.chars().enumerate() {
+            let valido = if i < 12 {
+                c.is_ascii_digit() || c.is_ascii_uppercase()
+            } else {
+                c.is_ascii_digit()
+            };
+            if !valido {
+                return Err(CnpjErro::Caractere(texto.to_string(), c));
+            }
+        }
+        let bytes = cnpj.as_bytes();
+        if bytes.iter().all(|c| *c == bytes[0]) {
+            return Err(CnpjErro::DigitoVerificador(texto.to_string()));
+        }
+        let primeiro = digito_verificador(&bytes[..12]);
+        let segundo = digito_verificador(&[&bytes[..12], &[primeiro + b'0']].concat());
+        if bytes[12] - b'0' != primeiro || bytes[13] - b'0' != segundo {
+            return Err(CnpjErro::DigitoVerificador(texto.to_string()));
+        }
+        Ok(Cnpj(cnpj))
+    }
+}
+
+impl fmt::Display for Cnpj {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.formatado())
+    }
+}
+
+impl Serialize for Cnpj {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.formatado())
+    }
+}
+
+impl<'de> Deserialize<'de> for Cnpj {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let texto = String::deserialize(deserializer)?;
+        texto.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn cnpj_valido() {
+        let cnpj: Cnpj = "00.177.445/0001-41".parse().unwrap();
+        assert_eq!(cnpj.digitos(), "00177445000141");
+        assert_eq!(cnpj.formatado(), "00.177.445/0001-41");
+        assert_eq!(cnpj.to_string(), "00.177.445/0001-41");
+        assert!(!cnpj.alfanumerico());
+        assert_eq!(
+            "45985371000108".parse::<Cnpj>().unwrap().formatado(),
+            "45.985.371/0001-08"
+        );
+    }
+    #[test]
+    fn cnpj_alfanumerico() {
+        // exemplo da Receita Federal
+        let cnpj: Cnpj = "12.ABC.345/01DE-35".parse().unwrap();
+        assert_eq!(cnpj.digitos(), "12ABC34501DE35");
+        assert!(cnpj.alfanumerico());
+        assert_eq!("12abc34501de35".parse::<Cnpj>(), Ok(cnpj));
+    }
+    #[test]
+    fn cnpj_invalido() {
+        assert_eq!(
+            "00.177.445/0001-42".parse::<Cnpj>(),
+            Err(CnpjErro::DigitoVerificador(
+                "00.177.445/0001-42".to_string()
+            ))
+        );
+        assert_eq!(
+            "11.111.111/1111-11".parse::<Cnpj>(),
+            Err(CnpjErro::DigitoVerificador(
+                "11.111.111/1111-11".to_string()
+            ))
+        );
+        assert_eq!(
+            "177445000141".parse::<Cnpj>(),
+            Err(CnpjErro::Tamanho("177445000141".to_string()))
+        );
+        assert_eq!(
+            "12.ABC.345/01DE-3A".parse::<Cnpj>(),
+            Err(CnpjErro::Caractere("12.ABC.345/01DE-3A".to_string(), 'A'))
+        );
+    }
+    #[test]
+    fn cnpj_serde() {
+        let cnpj: Cnpj = "00177445000141".parse().unwrap();
+        let json = serde_json::to_string(&cnpj).unwrap();
+        assert_eq!(json, "\"00.177.445/0001-41\"");
+        assert_eq!(serde_json::from_str::<Cnpj>(&json).unwrap(), cnpj);
+        assert!(serde_json::from_str::<Cnpj>("\"00.177.445/0001-42\"").is_err());
+    }
+}
@@ -0,0 +1,217 @@
+/*!
+Dúvidas registradas pelos usuários na página do CA (seção `lista_comentarios`).
+
+**Não verificado:** a página de exemplo (`SUCESSO`) não tem dúvidas, e não
+temos nenhuma página real com dúvidas salva. O formato lido por
+`Duvida::da_lista` é uma suposição e os testes usam HTML escrito à mão.
+Por isso o módulo é interno: as dúvidas não fazem parte da API pública nem
+da serialização do `CA` até que uma página real vire fixture. Enquanto isso,
+a extração só serve para registrar no relatório as listas que não seguem o
+formato suposto.
+*/
+#![allow(dead_code)]
+
+use crate::util::normalizar;
+use chrono::NaiveDate;
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+static DATA: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d{2}/\d{2}/\d{4}").unwrap());
+
+/// Aviso mostrado na lista quando o CA não tem dúvidas, já normalizado.
+const SEM_DUVIDAS: &str = "nao possui nenhuma duvida";
+
+/// Uma dúvida registrada na página do CA e as respostas que recebeu.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Duvida {
+    pub autor: Option<String>,
+    pub data: Option<NaiveDate>,
+    pub texto: String,
+    pub respostas: Vec<Resposta>,
+}
+
+/// Resposta a uma `Duvida`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Resposta {
+    pub autor: Option<String>,
+    pub data: Option<NaiveDate>,
+    pub texto: String,
+}
+
+impl Duvida {
+    /// Interpreta as dúvidas de `div#lista_comentarios`. Supomos (sem ter
+    /// visto uma página real com dúvidas) que cada dúvida é um `div` filho
+    /// da lista:
+    /// ```html
+    /// <div id="lista_comentarios">
+    ///   <div class="comentario">
+    ///     <p class="autor"><strong>JOÃO</strong> em 12/03/2024</p>
+    ///     <p class="texto">Esta calça é lavável?</p>
+    ///     <div class="resposta">
+    ///       <p class="autor"><strong>FARP</strong> em 13/03/2024</p>
+    ///       <p class="texto">Sim.</p>
+    ///     </div>
+    ///   </div>
+    /// </div>
+    /// ```
+    /// Quando não há dúvidas, a lista só tem um `p` com o aviso
+    /// "Este CA não possui nenhuma Dúvida registrada".
+    ///
+    /// Retorna um erro quando a lista tem conteúdo, mas nenhuma dúvida no
+    /// formato acima, ou quando uma dúvida fica sem texto.
+    pub(crate) fn da_lista(lista: ElementRef) -> Result<Vec<Duvida>, String> {
+        let resposta_selector = Selector::parse(".resposta").unwrap();
+        let duvidas = lista
+            .child_elements()
+            .filter(|e| e.value().name() == "div")
+            .map(|e| {
+                let (autor, data, texto) = mensagem(e);
+                Duvida {
+                    autor,
+                    data,
+                    texto,
+                    respostas: e
+                        .select(&resposta_selector)
+                        .map(|r| {
+                            let (autor, data, texto) = mensagem(r);
+                            Resposta { autor, data, texto }
+                        })
+                        .collect(),
+                }
+            })
+            .collect::<Vec<_>>();
+        if duvidas.iter().any(|d| d.texto.is_empty()) {
+            return Err("dúvida sem texto".to_string());
+        }
+        let texto = lista.text().collect::<String>();
+        if duvidas.is_empty()
+            && !texto.trim().is_empty()
+            && !normalizar(&texto).contains(SEM_DUVIDAS)
+        {
+            return Err("lista de dúvidas em formato desconhecido".to_string());
+        }
+        Ok(duvidas)
+    }
+}
+
+/// Retorna o autor, a data e o texto de uma mensagem. Só os filhos diretos
+/// de `elemento` são lidos, para que as respostas aninhadas fiquem de fora.
+fn mensagem(elemento: ElementRef) -> (Option<String>, Option<NaiveDate>, String) {
+    let strong_selector = Selector::parse("strong").unwrap();
+    let filhos = elemento
+        .child_elements()
+        .filter(|e| !tem_classe(e, "resposta"))
+        .collect::<Vec<_>>();
+    let cabecalho = filhos
+        .iter()
+        .find(|e| tem_classe(e, "autor") || tem_classe(e, "data"))
+        .or_else(|| filhos.first());
+    let autor = cabecalho
+        .and_then(|c| c.select(&strong_selector).next())
+        .map(|s| s.text().collect::<String>().trim().to_string())
+        .filter(|s| !s.is_empty());
+    let data = filhos
+        .iter()
+        .find_map(|e| {
+            DATA.find(&e.text().collect::<String>())
+                .map(|m| m.as_str().to_string())
+        })
+        .and_then(|d| NaiveDate::parse_from_str(&d, "%d/%m/%Y").ok());
+    let texto = match filhos.iter().find(|e| tem_classe(e, "texto")) {
+        Some(t) => t.text().collect::<String>().trim().to_string(),
+        None => filhos
+            .iter()
+            .filter(|e| Some(*e) != cabecalho)
+            .map(|e| e.text().collect::<String>().trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+    (autor, data, texto)
+}
+
+fn tem_classe(elemento: &ElementRef, classe: &str) -> bool {
+    elemento.value().classes().any(|c| c == classe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::Html;
+
+    fn duvidas(html: &str) -> Result<Vec<Duvida>, String> {
+        let fragmento = Html::parse_fragment(html);
+        let lista = fragmento
+            .select(&Selector::parse("#lista_comentarios").unwrap())
+            .next()
+            .unwrap();
+        Duvida::da_lista(lista)
+    }
+
+    #[test]
+    fn sem_duvidas() {
+        assert_eq!(
+            duvidas(
+                r#"<div id="lista_comentarios"><p>Este CA não possui nenhuma Dúvida registrada. Seja o primeiro a registrar!</p></div>"#
+            ),
+            Ok(vec![])
+        );
+        assert_eq!(
+            duvidas(r#"<div id="lista_comentarios"> </div>"#),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn formato_desconhecido() {
+        assert!(duvidas(
+            r#"<div id="lista_comentarios"><ul><li>JOÃO: Esta calça é lavável?</li></ul></div>"#
+        )
+        .is_err());
+        assert!(duvidas(
+            r#"<div id="lista_comentarios"><div class="comentario"><p><strong>JOÃO</strong></p></div></div>"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn duvidas_com_respostas() {
+        let html = r#"<div id="lista_comentarios">
+          <div class="comentario">
+            <p class="autor"><strong>JOÃO</strong> em 12/03/2024</p>
+            <p class="texto">Esta calça é lavável?</p>
+            <div class="resposta">
+              <p class="autor"><strong>FARP</strong> em 13/03/2024</p>
+              <p class="texto">Sim.</p>
+            </div>
+          </div>
+          <div class="comentario">
+            <p><strong>MARIA</strong></p>
+            <p>Qual a gramatura?</p>
+          </div>
+        </div>"#;
+        assert_eq!(
+            duvidas(html),
+            Ok(vec![
+                Duvida {
+                    autor: Some("JOÃO".to_string()),
+                    data: NaiveDate::from_ymd_opt(2024, 3, 12),
+                    texto: "Esta calça é lavável?".to_string(),
+                    respostas: vec![Resposta {
+                        autor: Some("FARP".to_string()),
+                        data: NaiveDate::from_ymd_opt(2024, 3, 13),
+                        texto: "Sim.".to_string(),
+                    }],
+                },
+                Duvida {
+                    autor: Some("MARIA".to_string()),
+                    data: None,
+                    texto: "Qual a gramatura?".to_string(),
+                    respostas: vec![],
+                },
+            ])
+        );
+    }
+}
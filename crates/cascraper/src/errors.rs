@@ -1,9 +1,78 @@
 use thiserror::Error;
 
-#[derive(Error, Debug, Eq, PartialEq)]
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
 pub enum CAError {
     /// Ocorre quando não se consegue encontrar a página
     /// do CA. Este erro contém o CA não encontrado.
     #[error("CA {0} não encontrado.")]
     NaoEncontrado(u32),
+    /// Ocorre quando não se consegue conectar ao site (DNS, conexão
+    /// recusada, TLS...). Este erro contém o CA e a mensagem do `reqwest`.
+    #[error("CA {0}: falha na conexão: {1}.")]
+    Conexao(u32, String),
+    /// Ocorre quando o site demora demais para responder.
+    /// Este erro contém o CA.
+    #[error("CA {0}: tempo esgotado.")]
+    TempoEsgotado(u32),
+    /// Ocorre quando o site responde com um status fora da faixa 2xx.
+    /// Este erro contém o CA e o código do status.
+    #[error("CA {0}: o site respondeu com o status HTTP {1}.")]
+    StatusHttp(u32, u16),
+    /// Ocorre quando o corpo da resposta não pode ser lido ou decodificado.
+    /// Este erro contém o CA e a mensagem do `reqwest`.
+    #[error("CA {0}: falha ao ler a resposta: {1}.")]
+    Corpo(u32, String),
+    /// Ocorre na consulta estrita quando campos obrigatórios estão
+    /// ausentes ou não puderam ser interpretados. Este erro contém
+    /// o CA e os nomes desses campos.
+    #[error("CA {0}: campos obrigatórios ausentes ou inválidos: {campos}.", campos = .1.join(", "))]
+    Incompleto(u32, Vec<String>),
+}
+
+impl CAError {
+    /// Diz se vale a pena repetir a consulta: falhas de rede, tempo
+    /// esgotado e os status 408, 425, 429 e 5xx (exceto 501). Um CA
+    /// não encontrado nunca é temporário.
+    pub fn e_temporario(&self) -> bool {
+        match self {
+            CAError::Conexao(..) | CAError::TempoEsgotado(_) | CAError::Corpo(..) => true,
+            CAError::StatusHttp(_, status) => {
+                matches!(status, 408 | 425 | 429) || (500..600).contains(status) && *status != 501
+            }
+            CAError::NaoEncontrado(_) | CAError::Incompleto(..) => false,
+        }
+    }
+}
+
+/// Erros na configuração de um `ClienteConsultaCA`.
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum ClienteErro {
+    /// A URL base não é válida. Contém a URL e o motivo.
+    #[error("URL base '{0}' inválida: {1}.")]
+    UrlInvalida(String, String),
+    /// O proxy não é válido. Contém o proxy e o motivo.
+    #[error("proxy '{0}' inválido: {1}.")]
+    Proxy(String, String),
+    /// O limite de requisições não é válido (veja
+    /// `LimiteRequisicoes::e_valido`). Contém o limite informado.
+    #[error("limite de requisições inválido: {0}.")]
+    LimiteInvalido(String),
+    /// O `reqwest` não conseguiu criar o cliente. Contém o motivo.
+    #[error("não foi possível criar o cliente HTTP: {0}.")]
+    Construcao(String),
+}
+
+/// Erros na interpretação de um CNPJ.
+/// Todos contêm o texto que foi interpretado.
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum CnpjErro {
+    /// O CNPJ não tem 14 caracteres (desconsiderando a pontuação).
+    #[error("CNPJ '{0}' não tem 14 caracteres.")]
+    Tamanho(String),
+    /// O CNPJ contém um caractere que não é permitido na sua posição.
+    #[error("CNPJ '{0}' contém o caractere inválido '{1}'.")]
+    Caractere(String, char),
+    /// Os dígitos verificadores não conferem.
+    #[error("CNPJ '{0}' tem dígitos verificadores inválidos.")]
+    DigitoVerificador(String),
 }
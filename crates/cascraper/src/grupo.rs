@@ -0,0 +1,194 @@
+/*!
+Grupo do EPI (proteção da cabeça, respiratória, ...), identificado pela
+imagem do título da página:
+```html
+<img class="grupo-epi-img" src="/images/grupo-epi-5.jpg" title="Proteção dos Membros Inferiores" />
+<span class="grupo-epi-desc">Proteção dos Membros Inferiores</span>
+```
+*/
+use crate::util::normalizar;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Grupo do EPI, conforme os grupos do anexo I da NR-6.
+/// Grupos que ainda não conhecemos caem em `Desconhecido`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GrupoEpi {
+    Cabeca,
+    OlhosEFace,
+    Auditiva,
+    Respiratoria,
+    Tronco,
+    MembrosSuperiores,
+    MembrosInferiores,
+    CorpoInteiro,
+    ContraQuedas,
+    /// Id da imagem (`grupo-epi-{id}.jpg`) e descrição, quando existem. O
+    /// id é guardado porque é estável mesmo quando a descrição muda.
+    Desconhecido(Option<u32>, Option<String>),
+}
+
+/// Ids das imagens `grupo-epi-{id}.jpg` já conferidos numa página real. O
+/// id é estável, mas a descrição às vezes muda de grafia, por isso ele tem
+/// prioridade. Ids fora desta tabela são identificados pela descrição.
+///
+/// Só entram ids vistos numa página salva (o 5 vem da página do CA 32551,
+/// em `pagina.rs`). A numeração do site não segue a ordem do anexo I da
+/// NR-6 (membros inferiores seria o 7), então os outros ids não podem ser
+/// deduzidos: para incluir um id, salve uma página do grupo e adicione um
+/// teste com ela.
+const IDS: &[(u32, GrupoEpi)] = &[(5, GrupoEpi::MembrosInferiores)];
+
+/// Trechos da descrição (normalizada) que identificam cada grupo.
+const DESCRICOES: &[(&str, GrupoEpi)] = &[
+    ("cabeca", GrupoEpi::Cabeca),
+    ("olhos", GrupoEpi::OlhosEFace),
+    ("auditiva", GrupoEpi::Auditiva),
+    ("respiratoria", GrupoEpi::Respiratoria),
+    ("tronco", GrupoEpi::Tronco),
+    ("membros superiores", GrupoEpi::MembrosSuperiores),
+    ("membros inferiores", GrupoEpi::MembrosInferiores),
+    ("corpo inteiro", GrupoEpi::CorpoInteiro),
+    ("queda", GrupoEpi::ContraQuedas),
+];
+
+impl GrupoEpi {
+    /// Identifica o grupo pelo `id` da imagem e, se o id não for
+    /// conhecido, pela `descricao`. Retorna `None` se não houver nenhum dos dois.
+    pub fn new(id: Option<u32>, descricao: Option<&str>) -> Option<Self> {
+        let do_id = id.and_then(|id| {
+            IDS.iter()
+                .find(|(conhecido, _)| *conhecido == id)
+                .map(|(_, grupo)| grupo.clone())
+        });
+        let da_descricao = || {
+            let descricao = normalizar(descricao?);
+            DESCRICOES
+                .iter()
+                .find(|(trecho, _)| descricao.contains(trecho))
+                .map(|(_, grupo)| grupo.clone())
+        };
+        let desconhecido = || match (id, descricao) {
+            (None, None) => None,
+            (id, descricao) => Some(GrupoEpi::Desconhecido(
+                id,
+                descricao.map(|d| d.trim().to_string()),
+            )),
+        };
+        do_id.or_else(da_descricao).or_else(desconhecido)
+    }
+
+    /// Extrai o id de um `src` como `/images/grupo-epi-5.jpg`.
+    pub(crate) fn id_da_imagem(src: &str) -> Option<u32> {
+        let (_, resto) = src.rsplit_once("grupo-epi-")?;
+        resto
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect::<String>()
+            .parse()
+            .ok()
+    }
+}
+
+impl fmt::Display for GrupoEpi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let descricao = match self {
+            GrupoEpi::Cabeca => "Proteção da Cabeça",
+            GrupoEpi::OlhosEFace => "Proteção dos Olhos e Face",
+            GrupoEpi::Auditiva => "Proteção Auditiva",
+            GrupoEpi::Respiratoria => "Proteção Respiratória",
+            GrupoEpi::Tronco => "Proteção do Tronco",
+            GrupoEpi::MembrosSuperiores => "Proteção dos Membros Superiores",
+            GrupoEpi::MembrosInferiores => "Proteção dos Membros Inferiores",
+            GrupoEpi::CorpoInteiro => "Proteção do Corpo Inteiro",
+            GrupoEpi::ContraQuedas => "Proteção Contra Quedas com Diferença de Nível",
+            GrupoEpi::Desconhecido(id, descricao) => {
+                return match (descricao, id) {
+                    (Some(descricao), _) => write!(f, "{descricao}"),
+                    (None, Some(id)) => write!(f, "grupo {id}"),
+                    (None, None) => write!(f, "grupo desconhecido"),
+                };
+            }
+        };
+        write!(f, "{descricao}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grupo_pelo_id_e_pela_descricao() {
+        assert_eq!(
+            GrupoEpi::new(Some(5), Some("Membros Inferiores (nova grafia)")),
+            Some(GrupoEpi::MembrosInferiores)
+        );
+        assert_eq!(
+            GrupoEpi::new(Some(77), Some("Proteção Respiratória")),
+            Some(GrupoEpi::Respiratoria)
+        );
+        assert_eq!(
+            GrupoEpi::new(None, Some("PROTEÇÃO DA CABEÇA")),
+            Some(GrupoEpi::Cabeca)
+        );
+        assert_eq!(
+            GrupoEpi::new(Some(77), Some("Proteção Estelar")),
+            Some(GrupoEpi::Desconhecido(
+                Some(77),
+                Some("Proteção Estelar".to_string())
+            ))
+        );
+        assert_eq!(
+            GrupoEpi::new(Some(77), None),
+            Some(GrupoEpi::Desconhecido(Some(77), None))
+        );
+        assert_eq!(
+            GrupoEpi::new(None, Some(" Proteção Estelar ")),
+            Some(GrupoEpi::Desconhecido(
+                None,
+                Some("Proteção Estelar".to_string())
+            ))
+        );
+        assert_eq!(GrupoEpi::new(None, None), None);
+        assert_eq!(
+            GrupoEpi::MembrosInferiores.to_string(),
+            "Proteção dos Membros Inferiores"
+        );
+        assert_eq!(
+            GrupoEpi::Desconhecido(Some(77), Some("Proteção Estelar".to_string())).to_string(),
+            "Proteção Estelar"
+        );
+        assert_eq!(
+            GrupoEpi::Desconhecido(Some(77), None).to_string(),
+            "grupo 77"
+        );
+    }
+
+    /// Id 5, da página do CA 32551 (`pagina::SUCESSO`).
+    #[test]
+    fn grupo_id_5() {
+        assert_eq!(
+            GrupoEpi::new(Some(5), None),
+            Some(GrupoEpi::MembrosInferiores)
+        );
+        let src = crate::pagina::SUCESSO
+            .split("src=\"")
+            .find_map(|resto| {
+                let src = resto.split('"').next()?;
+                src.contains("grupo-epi-").then_some(src)
+            })
+            .unwrap();
+        assert_eq!(GrupoEpi::id_da_imagem(src), Some(5));
+    }
+
+    #[test]
+    fn id_da_imagem() {
+        assert_eq!(GrupoEpi::id_da_imagem("/images/grupo-epi-5.jpg"), Some(5));
+        assert_eq!(
+            GrupoEpi::id_da_imagem("//consultaca.com/images/grupo-epi-12.png"),
+            Some(12)
+        );
+        assert_eq!(GrupoEpi::id_da_imagem("/images/logo.jpg"), None);
+    }
+}
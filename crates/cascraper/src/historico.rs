@@ -0,0 +1,52 @@
+/*!
+Tipos que representam o histórico de um CA (tabela "Histórico" da página).
+*/
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Uma linha da tabela "Histórico": a data da alteração e a ocorrência.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventoHistorico {
+    pub data: NaiveDate,
+    pub ocorrencia: Ocorrencia,
+}
+
+/// Ocorrência registrada no histórico do CA.
+/// Textos que ainda não conhecemos caem em `Outro`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Ocorrencia {
+    /// "Expedido"
+    Expedido,
+    /// "CA Vencido"
+    Vencido,
+    /// "CA Valido"
+    Valido,
+    Outro(String),
+}
+
+impl From<&str> for Ocorrencia {
+    fn from(texto: &str) -> Self {
+        let texto = texto.trim();
+        match texto.to_lowercase().as_str() {
+            "expedido" => Ocorrencia::Expedido,
+            "ca vencido" => Ocorrencia::Vencido,
+            "ca valido" | "ca válido" => Ocorrencia::Valido,
+            _ => Ocorrencia::Outro(texto.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ocorrencia;
+    #[test]
+    fn ocorrencia_from_str() {
+        assert_eq!(Ocorrencia::from(" Expedido "), Ocorrencia::Expedido);
+        assert_eq!(Ocorrencia::from("CA Vencido"), Ocorrencia::Vencido);
+        assert_eq!(Ocorrencia::from("CA Válido"), Ocorrencia::Valido);
+        assert_eq!(
+            Ocorrencia::from("CA Suspenso"),
+            Ocorrencia::Outro("CA Suspenso".to_string())
+        );
+    }
+}
@@ -1,6 +1,37 @@
+pub mod avaliacao;
+pub use avaliacao::Avaliacao;
 pub mod ca;
 pub use ca::pagina;
-pub use ca::CA;
+pub use ca::{CABuilder, Fabricante, FabricanteBuilder, Laudo, LaudoBuilder, TipoAvaliacao, CA};
+pub mod cliente;
+pub use cliente::{ClienteConsultaCA, ClienteConsultaCABuilder, Ordem};
+pub mod cnpj;
+pub use cnpj::Cnpj;
+mod duvida;
 pub mod errors;
+pub mod grupo;
+pub use grupo::GrupoEpi;
+pub mod historico;
+pub use historico::{EventoHistorico, Ocorrencia};
+pub mod limite;
+pub use limite::LimiteRequisicoes;
+pub mod modelo;
+pub use modelo::IndiceModelos;
+pub mod natureza;
+pub use natureza::Natureza;
+pub mod norma;
+pub use norma::NormaTecnica;
+pub mod ofertas;
+pub use ofertas::{ofertas, Classificacao, Oferta};
 mod pagina;
+pub mod pagina_ca;
+pub use pagina_ca::{PaginaCA, Secao, Tabela};
+pub mod relatorio;
+pub use relatorio::ExtracaoRelatorio;
+pub mod retentativa;
+pub use retentativa::PoliticaRetentativa;
+pub mod situacao;
+pub use situacao::Situacao;
 mod util;
+pub mod validade;
+pub use validade::Validade;
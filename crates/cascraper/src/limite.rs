@@ -0,0 +1,143 @@
+/*!
+Limite de requisições por segundo ao site do ConsultaCA.
+*/
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Quantas requisições podem ser feitas por segundo e quantas podem sair de
+/// uma vez (`rajada`) depois de um tempo sem requisições.
+///
+/// Com `por_segundo = 2.0` e `rajada = 5` (o padrão), um cliente parado
+/// pode fazer 5 requisições seguidas; depois disso, uma a cada meio segundo.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LimiteRequisicoes {
+    pub por_segundo: f64,
+    pub rajada: u32,
+}
+
+impl Default for LimiteRequisicoes {
+    fn default() -> Self {
+        LimiteRequisicoes::new(2.0, 5)
+    }
+}
+
+impl LimiteRequisicoes {
+    pub fn new(por_segundo: f64, rajada: u32) -> Self {
+        LimiteRequisicoes {
+            por_segundo,
+            rajada,
+        }
+    }
+
+    /// Diz se o limite pode ser aplicado: a taxa deve ser positiva e
+    /// finita e a rajada deve ser de pelo menos uma requisição.
+    pub fn e_valido(&self) -> bool {
+        self.por_segundo.is_finite() && self.por_segundo > 0.0 && self.rajada >= 1
+    }
+}
+
+/// Balde de fichas compartilhado por todas as cópias de um
+/// `ClienteConsultaCA`. Cada requisição gasta uma ficha, e as fichas voltam
+/// à taxa de `por_segundo`, até o máximo de `rajada`.
+#[derive(Debug, Clone)]
+pub(crate) struct Limitador {
+    limite: LimiteRequisicoes,
+    balde: Arc<Mutex<Balde>>,
+}
+
+#[derive(Debug)]
+struct Balde {
+    /// Fichas disponíveis. Fica negativo quando há requisições na fila.
+    fichas: f64,
+    atualizado: Instant,
+}
+
+impl Limitador {
+    pub(crate) fn new(limite: LimiteRequisicoes) -> Self {
+        Limitador {
+            limite,
+            balde: Arc::new(Mutex::new(Balde {
+                fichas: limite.rajada as f64,
+                atualizado: Instant::now(),
+            })),
+        }
+    }
+
+    pub(crate) fn limite(&self) -> LimiteRequisicoes {
+        self.limite
+    }
+
+    /// Espera até que uma requisição possa ser feita. Quem chega primeiro
+    /// reserva a ficha primeiro, então a ordem de chegada é respeitada.
+    pub(crate) async fn aguardar(&self) {
+        let espera = {
+            let mut balde = self.balde.lock().unwrap();
+            let agora = Instant::now();
+            let decorrido = agora.duration_since(balde.atualizado).as_secs_f64();
+            balde.fichas =
+                (balde.fichas + decorrido * self.limite.por_segundo).min(self.limite.rajada as f64);
+            balde.atualizado = agora;
+            balde.fichas -= 1.0;
+            if balde.fichas >= 0.0 {
+                return;
+            }
+            // com uma taxa muito pequena a espera não cabe num `Duration`
+            Duration::try_from_secs_f64(-balde.fichas / self.limite.por_segundo)
+                .unwrap_or(Duration::MAX)
+        };
+        tokio::time::sleep(espera).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validade_do_limite() {
+        assert!(LimiteRequisicoes::new(0.5, 1).e_valido());
+        assert!(!LimiteRequisicoes::new(0.0, 1).e_valido());
+        assert!(!LimiteRequisicoes::new(f64::INFINITY, 1).e_valido());
+        assert!(!LimiteRequisicoes::new(2.0, 0).e_valido());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rajada_e_taxa() {
+        let limitador = Limitador::new(LimiteRequisicoes::new(20.0, 3));
+        let inicio = Instant::now();
+        for _ in 0..3 {
+            limitador.aguardar().await;
+        }
+        assert_eq!(inicio.elapsed(), Duration::ZERO);
+        // as cópias compartilham o mesmo balde
+        let copia = limitador.clone();
+        let tarefas = (0..4)
+            .map(|_| {
+                let copia = copia.clone();
+                tokio::spawn(async move { copia.aguardar().await })
+            })
+            .collect::<Vec<_>>();
+        for tarefa in tarefas {
+            tarefa.await.unwrap();
+        }
+        // uma ficha a cada 50ms
+        assert_eq!(inicio.elapsed(), Duration::from_millis(200));
+
+        // parado, o balde volta a encher, mas só até a rajada
+        tokio::time::advance(Duration::from_secs(10)).await;
+        let inicio = Instant::now();
+        for _ in 0..4 {
+            limitador.aguardar().await;
+        }
+        assert_eq!(inicio.elapsed(), Duration::from_millis(50));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn taxa_muito_pequena() {
+        let limitador = Limitador::new(LimiteRequisicoes::new(1e-20, 1));
+        limitador.aguardar().await;
+        let espera = tokio::time::timeout(Duration::from_secs(3600), limitador.aguardar());
+        assert!(espera.await.is_err());
+    }
+}
@@ -0,0 +1,243 @@
+/*!
+Códigos de modelo do campo "Referências" e busca de CAs pelo modelo.
+
+Os fornecedores costumam informar o modelo do EPI ("3M 8720") e não o CA.
+`IndiceModelos` responde qual CA cobre um modelo.
+*/
+use crate::util::normalizar;
+use crate::{Fabricante, CA};
+use std::collections::HashMap;
+
+/// Prefixos que às vezes antecedem os códigos ("Ref.: 8720", "Modelo 8720").
+const PREFIXOS: &[&str] = &[
+    "REFERENCIAS",
+    "REFERENCIA",
+    "REF",
+    "MODELOS",
+    "MODELO",
+    "MOD",
+];
+
+/// Separa o texto de "Referências" em códigos de modelo normalizados
+/// (maiúsculas, sem espaços repetidos e sem o ponto final).
+/// Os códigos são separados por `,`, `;`, ` / ` ou quebra de linha. O `e`
+/// só separa quando os dois lados têm algum dígito (`"8710 e 9920"`,
+/// `"CG 10 e CG 20"`), para não quebrar nomes como `"Luva de Couro e Raspa"`.
+/// Um prefixo igual a uma das `marcas` é removido: com a marca `"3M"`,
+/// `"3M 8720"` vira `"8720"`.
+pub fn modelos(referencias: &str, marcas: &[&str]) -> Vec<String> {
+    let marcas = marcas
+        .iter()
+        .map(|m| normalizar(m).to_uppercase())
+        .filter(|m| !m.is_empty())
+        .collect::<Vec<_>>();
+    referencias
+        .replace(" / ", ";")
+        .split([',', ';', '\n'])
+        .flat_map(separar_no_e)
+        .filter_map(|parte| {
+            let mut modelo = parte.trim_end_matches('.').to_uppercase();
+            modelo = sem_prefixo(&modelo, PREFIXOS);
+            modelo = sem_prefixo(
+                &modelo,
+                &marcas.iter().map(|m| m.as_str()).collect::<Vec<_>>(),
+            );
+            (!modelo.is_empty()).then_some(modelo)
+        })
+        .collect()
+}
+
+/// Separa `parte` nos `e` entre dois códigos e junta as palavras de cada
+/// pedaço com um espaço só. Um lado é código se tem algum dígito; o lado
+/// direito vai até o próximo `e`.
+fn separar_no_e(parte: &str) -> Vec<String> {
+    let e_conjuncao = |palavra: &&str| palavra.eq_ignore_ascii_case("e");
+    let tem_digito = |palavras: &[&str]| {
+        palavras
+            .iter()
+            .any(|p| p.chars().any(|c| c.is_ascii_digit()))
+    };
+    let palavras = parte.split_whitespace().collect::<Vec<_>>();
+    let mut pedacos = vec![vec![]];
+    for (i, palavra) in palavras.iter().enumerate() {
+        let direita = palavras[i + 1..]
+            .split(e_conjuncao)
+            .next()
+            .unwrap_or_default();
+        let separa =
+            e_conjuncao(palavra) && tem_digito(pedacos.last().unwrap()) && tem_digito(direita);
+        if separa {
+            pedacos.push(vec![]);
+        } else {
+            pedacos.last_mut().unwrap().push(*palavra);
+        }
+    }
+    pedacos.into_iter().map(|p| p.join(" ")).collect()
+}
+
+/// Remove um dos `prefixos` (seguido de espaço, `.` ou `:`) do começo do código.
+fn sem_prefixo(modelo: &str, prefixos: &[&str]) -> String {
+    for prefixo in prefixos {
+        if let Some(resto) = modelo.strip_prefix(prefixo) {
+            if resto.starts_with([' ', '.', ':']) {
+                return resto.trim_start_matches([' ', '.', ':']).to_string();
+            }
+        }
+    }
+    modelo.to_string()
+}
+
+/// Chave de busca: só letras e números, sem acentos e em maiúsculas, para
+/// que "8720-N", "8720 N" e "8720n" sejam o mesmo modelo.
+fn chave(modelo: &str) -> String {
+    normalizar(modelo)
+        .to_uppercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+/// Índice dos CAs pelos seus códigos de modelo.
+/// ```
+/// use cascraper::{modelo::IndiceModelos, Fabricante, CA};
+///
+/// let ca = CA::builder(445)
+///     .referencias("3M 8720")
+///     .modelo("8720")
+///     .fabricante(Fabricante::builder().nome_fantasia("3M").build())
+///     .build();
+/// let indice = IndiceModelos::new([&ca]);
+/// assert_eq!(indice.buscar("8720", Some("3M")), vec![445]);
+/// assert_eq!(indice.buscar("3M 8720", None), vec![445]);
+/// assert!(indice.buscar("8720", Some("MSA")).is_empty());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct IndiceModelos {
+    /// chave do modelo -> (CA, marcas do fabricante)
+    modelos: HashMap<String, Vec<(u32, Vec<String>)>>,
+}
+
+impl IndiceModelos {
+    pub fn new<'a, I: IntoIterator<Item = &'a CA>>(cas: I) -> Self {
+        let mut indice = IndiceModelos::default();
+        for ca in cas {
+            indice.adicionar(ca);
+        }
+        indice
+    }
+
+    /// Adiciona os modelos de um CA ao índice.
+    pub fn adicionar(&mut self, ca: &CA) {
+        let marcas = marcas(ca.fabricante())
+            .iter()
+            .map(|m| chave(m))
+            .collect::<Vec<_>>();
+        for modelo in ca.modelos() {
+            let entradas = self.modelos.entry(chave(modelo)).or_default();
+            if !entradas.iter().any(|(numero, _)| *numero == ca.ca) {
+                entradas.push((ca.ca, marcas.clone()));
+            }
+        }
+    }
+
+    /// CAs que cobrem o `modelo`, em ordem crescente. Se a `marca` for
+    /// informada, só entram CAs cujo fabricante tem essa marca (nome
+    /// fantasia ou primeira palavra da razão social). A marca também pode
+    /// vir no começo do modelo (`"3M 8720"`).
+    pub fn buscar(&self, modelo: &str, marca: Option<&str>) -> Vec<u32> {
+        let marca = marca.map(chave);
+        let mut cas = self
+            .modelos
+            .get(&chave(modelo))
+            .into_iter()
+            .flatten()
+            .filter(|(_, marcas)| marca.as_ref().is_none_or(|m| marcas.contains(m)))
+            .map(|(ca, _)| *ca)
+            .collect::<Vec<_>>();
+        if cas.is_empty() && marca.is_none() {
+            // "3M 8720": tenta separar a marca do modelo
+            if let Some((marca, modelo)) = modelo.trim().split_once(char::is_whitespace) {
+                cas = self.buscar(modelo, Some(marca));
+            }
+        }
+        cas.sort_unstable();
+        cas.dedup();
+        cas
+    }
+}
+
+/// Marcas do fabricante: o nome fantasia e a primeira palavra da razão social.
+pub(crate) fn marcas(fabricante: &Fabricante) -> Vec<String> {
+    let mut marcas = vec![];
+    if let Some(nome_fantasia) = fabricante.nome_fantasia() {
+        marcas.push(nome_fantasia.to_string());
+    }
+    if let Some(palavra) = fabricante
+        .razao_social()
+        .and_then(|r| r.split_whitespace().next())
+    {
+        marcas.push(palavra.to_string());
+    }
+    marcas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modelos_das_referencias() {
+        assert_eq!(modelos("3M 8720", &["3M"]), vec!["8720"]);
+        assert_eq!(
+            modelos("F23.16", &["FARP UNIFORMES", "FARP"]),
+            vec!["F23.16"]
+        );
+        assert_eq!(
+            modelos("Ref.: 8720, 8720N; 8710 e  9920 / PFF-2.", &[]),
+            vec!["8720", "8720N", "8710", "9920", "PFF-2"]
+        );
+        assert_eq!(modelos("Modelo volk 45 ", &[]), vec!["VOLK 45"]);
+        // "e" entre palavras sem dígitos faz parte do nome
+        assert_eq!(
+            modelos("Luva de Couro e Raspa; Luva Vaqueta E Raspa 10", &[]),
+            vec!["LUVA DE COURO E RASPA", "LUVA VAQUETA E RASPA 10"]
+        );
+        assert_eq!(modelos("CG 10 E CG 20", &[]), vec!["CG 10", "CG 20"]);
+        assert_eq!(modelos("", &["3M"]), Vec::<String>::new());
+        // a marca só é removida quando é um prefixo separado
+        assert_eq!(modelos("3M8720", &["3M"]), vec!["3M8720"]);
+    }
+
+    #[test]
+    fn busca_por_modelo() {
+        let ca = |numero, modelos: &[&str], nome_fantasia, razao_social| {
+            let mut builder = CA::builder(numero).fabricante(
+                Fabricante::builder()
+                    .nome_fantasia(nome_fantasia)
+                    .razao_social(razao_social)
+                    .build(),
+            );
+            for modelo in modelos {
+                builder = builder.modelo(*modelo);
+            }
+            builder.build()
+        };
+        let cas = [
+            ca(445, &["8720", "8710"], "3M", "3M DO BRASIL LTDA"),
+            ca(
+                32551,
+                &["F23.16"],
+                "FARP UNIFORMES",
+                "FARP INDUSTRIA DE ROUPAS LTDA",
+            ),
+            ca(99999, &["8720"], "MSA", "MSA DO BRASIL"),
+        ];
+        let indice = IndiceModelos::new(&cas);
+        assert_eq!(indice.buscar("8720", None), vec![445, 99999]);
+        assert_eq!(indice.buscar("8720", Some("3m")), vec![445]);
+        assert_eq!(indice.buscar("3M 8720", None), vec![445]);
+        assert_eq!(indice.buscar("f23 16", None), vec![32551]);
+        assert_eq!(indice.buscar("F23.16", Some("FARP")), vec![32551]);
+        assert!(indice.buscar("9999", None).is_empty());
+    }
+}
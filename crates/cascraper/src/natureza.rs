@@ -0,0 +1,38 @@
+/*!
+Natureza do EPI conforme exibida na página ("Nacional" ou "Importado").
+*/
+use serde::{Deserialize, Serialize};
+
+/// Origem do EPI.
+/// Textos que ainda não conhecemos caem em `Desconhecido`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Natureza {
+    Nacional,
+    Importado,
+    Desconhecido(String),
+}
+
+impl From<&str> for Natureza {
+    fn from(texto: &str) -> Self {
+        let texto = texto.trim();
+        match texto.to_lowercase().as_str() {
+            "nacional" => Natureza::Nacional,
+            "importado" | "importada" => Natureza::Importado,
+            _ => Natureza::Desconhecido(texto.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Natureza;
+    #[test]
+    fn natureza_from_str() {
+        assert_eq!(Natureza::from("Nacional"), Natureza::Nacional);
+        assert_eq!(Natureza::from(" IMPORTADO "), Natureza::Importado);
+        assert_eq!(
+            Natureza::from("Nacional/Importado"),
+            Natureza::Desconhecido("Nacional/Importado".to_string())
+        );
+    }
+}
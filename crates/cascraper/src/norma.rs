@@ -0,0 +1,230 @@
+/*!
+Normas técnicas citadas na seção "Normas" da página do CA.
+*/
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Órgãos que emitem normas, na forma em que aparecem na página.
+const ORGAOS: &[&str] = &[
+    "ABNT", "NBR", "ASTM", "EN", "ISO", "IEC", "BS", "DIN", "ANSI", "ISEA", "NFPA", "NIOSH", "CSA",
+    "AS", "NZS", "UNE", "NF", "IEEE",
+];
+
+/// Uma norma técnica, como "ABNT NBR 13698:2011" ou "ASTM F 1506-10a".
+///
+/// A página escreve a mesma norma de formas diferentes ("ASTM D 6413:2015",
+/// "ASTM D6413-15"), então as comparações devem usar `orgao`, `numero`,
+/// `parte` e `edicao`, e não o `texto`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NormaTecnica {
+    /// Órgão emissor (`"ABNT NBR"`, `"ASTM"`, `"BS EN"`, `"ANSI/ISEA"`...).
+    pub orgao: Option<String>,
+    /// Número da norma (`"13698"`, `"D6413"`, `"F1959/F1959M"`). Nas normas
+    /// da ASTM os espaços são removidos.
+    pub numero: String,
+    /// Parte da norma (o `5` de "EN 1149-5").
+    pub parte: Option<String>,
+    /// Ano ou revisão, como escrito (`"2011"`, `"10a"`, `"14"`).
+    pub edicao: Option<String>,
+    /// Texto original da página.
+    pub texto: String,
+}
+
+impl NormaTecnica {
+    /// Órgão, número e parte, sem a edição: `"ASTM D6413"`, `"BS EN 1149-5"`.
+    /// Duas edições da mesma norma têm a mesma designação.
+    pub fn designacao(&self) -> String {
+        let mut designacao = match &self.orgao {
+            Some(orgao) => format!("{orgao} {}", self.numero),
+            None => self.numero.clone(),
+        };
+        if let Some(parte) = &self.parte {
+            designacao.push('-');
+            designacao.push_str(parte);
+        }
+        designacao
+    }
+
+    /// Ano da edição com quatro dígitos. As revisões da ASTM têm só os
+    /// dois últimos dígitos do ano (`"10a"` é 2010).
+    pub fn ano(&self) -> Option<u16> {
+        let digitos = self
+            .edicao
+            .as_deref()?
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect::<String>();
+        let ano = digitos.parse::<u16>().ok()?;
+        match digitos.len() {
+            4 => Some(ano),
+            2 if ano >= 50 => Some(1900 + ano),
+            2 => Some(2000 + ano),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for NormaTecnica {
+    fn from(texto: &str) -> Self {
+        let texto = texto.trim();
+        let palavras = texto.split_whitespace().collect::<Vec<&str>>();
+        let qtd_orgaos = palavras
+            .iter()
+            .take_while(|p| p.split('/').all(|o| ORGAOS.contains(&o)))
+            .count();
+        let mut orgao = palavras[..qtd_orgaos].join(" ");
+        if orgao == "NBR" {
+            orgao = "ABNT NBR".to_string();
+        }
+        let resto = palavras[qtd_orgaos..].join(" ");
+        let astm = orgao == "ASTM";
+
+        let (designacao, edicao) = match resto.split_once(':') {
+            Some((designacao, edicao)) => (designacao.to_string(), Some(edicao.trim())),
+            None => match resto.rsplit_once('-') {
+                // "F 1506-10a" (ASTM) e "Z89.1-2014"
+                Some((designacao, edicao)) if astm || e_ano(edicao) => {
+                    (designacao.to_string(), Some(edicao.trim()))
+                }
+                _ => (resto.clone(), None),
+            },
+        };
+        let (numero, parte) = match designacao.split_once('-') {
+            Some((numero, parte)) if !astm => (numero, Some(parte.trim().to_string())),
+            _ => (designacao.as_str(), None),
+        };
+        NormaTecnica {
+            orgao: (!orgao.is_empty()).then_some(orgao),
+            // "D 6413" e "D6413" são a mesma norma da ASTM
+            numero: if astm {
+                numero.split_whitespace().collect()
+            } else {
+                numero.split_whitespace().collect::<Vec<_>>().join(" ")
+            },
+            parte,
+            edicao: edicao.filter(|e| !e.is_empty()).map(|e| e.to_string()),
+            texto: texto.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for NormaTecnica {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.designacao())?;
+        if let Some(edicao) = &self.edicao {
+            // a ASTM separa a revisão com hífen: "ASTM F1506-10a"
+            let separador = match self.orgao.as_deref() {
+                Some("ASTM") => '-',
+                _ => ':',
+            };
+            write!(f, "{separador}{edicao}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Diz se o texto é um ano de quatro dígitos.
+fn e_ano(texto: &str) -> bool {
+    texto.len() == 4 && texto.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn norma(
+        orgao: Option<&str>,
+        numero: &str,
+        parte: Option<&str>,
+        edicao: Option<&str>,
+        texto: &str,
+    ) -> NormaTecnica {
+        NormaTecnica {
+            orgao: orgao.map(|o| o.to_string()),
+            numero: numero.to_string(),
+            parte: parte.map(|p| p.to_string()),
+            edicao: edicao.map(|e| e.to_string()),
+            texto: texto.to_string(),
+        }
+    }
+
+    #[test]
+    fn normas_from_str() {
+        let casos = [
+            norma(
+                Some("ABNT NBR"),
+                "13698",
+                None,
+                Some("2011"),
+                "ABNT NBR 13698:2011",
+            ),
+            norma(
+                Some("ABNT NBR"),
+                "16064",
+                None,
+                Some("2016"),
+                "NBR 16064:2016",
+            ),
+            norma(
+                Some("ASTM"),
+                "D6413",
+                None,
+                Some("2015"),
+                "ASTM D 6413:2015",
+            ),
+            norma(Some("ASTM"), "F1506", None, Some("10a"), "ASTM F 1506-10a"),
+            norma(
+                Some("ASTM"),
+                "F1959/F1959M",
+                None,
+                Some("14"),
+                "ASTM F1959/F1959M-14",
+            ),
+            norma(Some("ASTM"), "F2621", None, Some("19"), "ASTM F2621-19"),
+            norma(
+                Some("BS EN"),
+                "1149",
+                Some("5"),
+                Some("2008"),
+                "BS EN 1149-5:2008",
+            ),
+            norma(
+                Some("EN ISO"),
+                "20471",
+                None,
+                Some("2013+A1:2016"),
+                "EN ISO 20471:2013+A1:2016",
+            ),
+            norma(
+                Some("ANSI/ISEA"),
+                "Z89.1",
+                None,
+                Some("2014"),
+                "ANSI/ISEA Z89.1-2014",
+            ),
+            norma(None, "Portaria 672", None, None, "Portaria 672"),
+        ];
+        for esperado in casos {
+            assert_eq!(NormaTecnica::from(esperado.texto.as_str()), esperado);
+        }
+    }
+
+    #[test]
+    fn designacao_e_ano() {
+        let a = NormaTecnica::from("ASTM D 6413:2015");
+        let b = NormaTecnica::from("ASTM D6413-99");
+        assert_eq!(a.designacao(), b.designacao());
+        assert_eq!(a.ano(), Some(2015));
+        assert_eq!(b.ano(), Some(1999));
+        assert_eq!(NormaTecnica::from("ASTM F 1506-10a").ano(), Some(2010));
+        assert_eq!(
+            NormaTecnica::from("BS EN 1149-5:2008").to_string(),
+            "BS EN 1149-5:2008"
+        );
+        assert_eq!(
+            NormaTecnica::from("ASTM F 1506-10a").to_string(),
+            "ASTM F1506-10a"
+        );
+        assert_eq!(NormaTecnica::from("Portaria 672").ano(), None);
+    }
+}
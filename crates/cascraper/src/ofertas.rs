@@ -0,0 +1,462 @@
+/*!
+Extrai as ofertas do BuscaEPI que aparecem no carrossel da página do CA.
+*/
+use crate::util::normalizar;
+use crate::CA;
+use log::warn;
+use regex::Regex;
+use rust_decimal::Decimal;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+/// Encontra menções a CAs em nomes de produtos: `CA 30977`, `C.A 46298`,
+/// `CA: 123`, `CA 38561 / 41282`...
+static CA_NO_NOME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\bC\.?\s?A\b\.?\s*(?:n[º°o]\.?\s*)?:?\s*(\d+(?:\s*/\s*\d+)*)").unwrap()
+});
+
+/// Uma oferta do carrossel "Ofertas BuscaEPI relacionadas a este EPI".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Oferta {
+    /// Id da oferta no BuscaEPI (o `2288` de `/acessar-oferta/2288`).
+    pub id: Option<u32>,
+    /// Nome da loja (`span.nome-loja`).
+    pub loja: String,
+    /// Nome do produto (`span.nome`).
+    pub nome: String,
+    /// CA declarado pela loja (`span.ca`).
+    pub ca: Option<u32>,
+    /// Preço antes do desconto, em reais.
+    pub preco_de: Option<Decimal>,
+    /// Preço atual, em reais.
+    pub preco_por: Option<Decimal>,
+    /// Link para a oferta no BuscaEPI.
+    pub link: Option<String>,
+}
+
+/// Resultado da comparação entre os CAs de uma oferta e o CA da página.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Classificacao {
+    /// Os CAs citados pela oferta batem entre si e o produto é do mesmo
+    /// tipo do equipamento da página.
+    Consistente,
+    /// A oferta pode estar usando um CA que não é do produto. Contém o motivo.
+    Suspeita(String),
+    /// O produto não tem relação com o equipamento da página
+    /// (ex.: uma `Lixeira 360L` na página de uma calça).
+    NaoRelacionada,
+}
+
+impl Oferta {
+    /// CAs citados no nome do produto, na ordem em que aparecem.
+    /// Um número cortado no fim do nome (`... CA 4` com CA declarado `45108`)
+    /// é ignorado, pois o site trunca nomes longos.
+    pub fn cas_no_nome(&self) -> Vec<u32> {
+        let mut cas = Vec::new();
+        for captura in CA_NO_NOME.captures_iter(&self.nome) {
+            let numeros = captura.get(1).unwrap();
+            let truncado = numeros.end() == self.nome.trim_end().len();
+            for numero in numeros.as_str().split('/') {
+                let numero = numero.trim();
+                let cortado = truncado
+                    && self.ca.is_some_and(|ca| {
+                        ca.to_string().starts_with(numero) && ca.to_string() != numero
+                    });
+                if cortado {
+                    continue;
+                }
+                if let Ok(ca) = numero.parse() {
+                    cas.push(ca);
+                }
+            }
+        }
+        cas
+    }
+
+    /// Compara o CA declarado pela loja e os CAs citados no nome do produto
+    /// com o CA da página (`ca`).
+    ///
+    /// - É `Suspeita` a oferta cujo nome cita CAs diferentes do declarado, ou
+    ///   que usa o CA da página num produto de outro tipo.
+    /// - É `NaoRelacionada` a oferta de outro CA cujo produto não é do tipo
+    ///   do equipamento da página.
+    /// - As demais são `Consistente`.
+    ///
+    /// O tipo do equipamento é a primeira palavra da descrição do CA
+    /// (`CALÇA`, `LUVA`, `RESPIRADOR`...), que tem de aparecer no nome do
+    /// produto, sem diferenciar acentos, maiúsculas e plural (`LUVAS` e
+    /// `Luva` são do mesmo tipo; `CALÇA` e `Calçado`, não). Sem descrição,
+    /// todo produto é considerado do mesmo tipo.
+    pub fn classificar(&self, ca: &CA) -> Classificacao {
+        let cas_no_nome = self.cas_no_nome();
+        if let Some(declarado) = self.ca {
+            if !cas_no_nome.is_empty() && !cas_no_nome.contains(&declarado) {
+                let citados = cas_no_nome
+                    .iter()
+                    .map(|ca| ca.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return Classificacao::Suspeita(format!(
+                    "o nome cita o CA {citados}, mas a loja declara o CA {declarado}"
+                ));
+            }
+        }
+        let usa_o_ca = self.ca == Some(ca.ca) || cas_no_nome.contains(&ca.ca);
+        let mesmo_tipo = ca
+            .descricao()
+            .is_none_or(|descricao| mesmo_tipo(descricao, &self.nome));
+        match (usa_o_ca, mesmo_tipo) {
+            (true, false) => Classificacao::Suspeita(format!(
+                "usa o CA {}, mas o produto não é do tipo '{}'",
+                ca.ca,
+                ca.descricao().unwrap_or_default()
+            )),
+            (false, false) => Classificacao::NaoRelacionada,
+            _ => Classificacao::Consistente,
+        }
+    }
+}
+
+/// Diz se o nome do produto contém a primeira palavra da descrição do CA,
+/// comparando as palavras sem acentos e no singular.
+fn mesmo_tipo(descricao: &str, nome: &str) -> bool {
+    let descricao = normalizar(descricao);
+    let Some(tipo) = descricao
+        .split(|c: char| !c.is_alphanumeric())
+        .find(|palavra| palavra.chars().count() >= 3)
+        .map(singular)
+    else {
+        return true;
+    };
+    normalizar(nome)
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|palavra| singular(palavra) == tipo)
+}
+
+/// Singular de uma palavra já normalizada (sem acentos e em minúsculas),
+/// pelas regras de plural mais comuns nos nomes de EPI: `luvas` vira
+/// `luva`, `respiradores` vira `respirador`, `cinturoes` vira `cinturao`
+/// e `aventais` vira `avental`. Não precisa ser correto para toda palavra,
+/// só dar o mesmo resultado para o singular e o plural.
+fn singular(palavra: &str) -> String {
+    const REGRAS: &[(&str, &str)] = &[
+        ("oes", "ao"),
+        ("aes", "ao"),
+        ("ais", "al"),
+        ("eis", "el"),
+        ("ois", "ol"),
+        ("ns", "m"),
+        ("res", "r"),
+        ("zes", "z"),
+        ("ses", "s"),
+    ];
+    if palavra.chars().count() <= 3 {
+        return palavra.to_string();
+    }
+    for (plural, singular) in REGRAS {
+        if let Some(raiz) = palavra.strip_suffix(plural) {
+            return format!("{raiz}{singular}");
+        }
+    }
+    palavra
+        .strip_suffix('s')
+        .filter(|raiz| !raiz.ends_with('s'))
+        .unwrap_or(palavra)
+        .to_string()
+}
+
+/// Retorna as ofertas do carrossel da página do CA.
+/// Cada oferta tem a seguinte forma:
+/// ```html
+/// <div class="produto">
+///   <a href="https://buscaepi.com/acessar-oferta/2288?medium=consultaca">
+///     <span class="nome-loja"><strong>SUPER EPI</strong></span>
+///     <span class="ca">CA: <strong>30977</strong></span>
+///     <span class="nome">Calça NR10 Risco 2 ...</span>
+///     <span class="preco_de">de R$ 237,51</span>
+///     <span class="preco_por">por <strong>R$ 218,51</strong></span>
+///   </a>
+/// </div>
+/// ```
+pub fn ofertas(body: &Html) -> Vec<Oferta> {
+    let selector = Selector::parse(".ofertas div.produto").unwrap();
+    body.select(&selector).map(oferta).collect()
+}
+
+fn oferta(produto: ElementRef) -> Oferta {
+    let a_selector = Selector::parse("a[href]").unwrap();
+    let link = produto
+        .select(&a_selector)
+        .next()
+        .and_then(|a| a.attr("href"))
+        .map(|href| href.to_string());
+    let ca = texto(produto, "span.ca").and_then(|ca| {
+        ca.chars()
+            .filter(|c| c.is_ascii_digit())
+            .collect::<String>()
+            .parse::<u32>()
+            .ok()
+    });
+    Oferta {
+        id: link.as_deref().and_then(id_da_oferta),
+        loja: texto(produto, "span.nome-loja").unwrap_or_default(),
+        nome: texto(produto, "span.nome").unwrap_or_default(),
+        ca,
+        preco_de: texto(produto, "span.preco_de").and_then(|p| preco(&p)),
+        preco_por: texto(produto, "span.preco_por").and_then(|p| preco(&p)),
+        link,
+    }
+}
+
+/// Texto (sem espaços nas pontas) do primeiro elemento que corresponde ao
+/// `seletor`. Retorna `None` se o elemento não existe ou está vazio.
+fn texto(produto: ElementRef, seletor: &str) -> Option<String> {
+    let selector = Selector::parse(seletor).unwrap();
+    let texto = produto
+        .select(&selector)
+        .next()?
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_string();
+    if texto.is_empty() {
+        None
+    } else {
+        Some(texto)
+    }
+}
+
+/// Extrai o id de um link como `https://buscaepi.com/acessar-oferta/2288?medium=consultaca`.
+fn id_da_oferta(link: &str) -> Option<u32> {
+    let (_, resto) = link.split_once("/acessar-oferta/")?;
+    resto
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>()
+        .parse()
+        .ok()
+}
+
+/// Converte um texto como `de R$ 1.237,51` para `1237.51`.
+fn preco(texto: &str) -> Option<Decimal> {
+    let numero = texto
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == ',')
+        .collect::<String>()
+        .replace(',', ".");
+    if numero.is_empty() {
+        return None;
+    }
+    match Decimal::from_str(&numero) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            warn!("oferta: preço '{texto}' inválido. Veja: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pagina::SUCESSO;
+
+    #[test]
+    fn precos() {
+        assert_eq!(preco("de R$ 237,51"), Some(Decimal::new(23751, 2)));
+        assert_eq!(preco("por R$ 1.977,00"), Some(Decimal::new(197700, 2)));
+        assert_eq!(preco("R$ 15"), Some(Decimal::new(15, 0)));
+        assert_eq!(preco(""), None);
+        assert_eq!(preco("R$ 1,2,3"), None);
+    }
+
+    fn oferta(nome: &str, ca: Option<u32>) -> Oferta {
+        Oferta {
+            id: None,
+            loja: "LOJA".to_string(),
+            nome: nome.to_string(),
+            ca,
+            preco_de: None,
+            preco_por: None,
+            link: None,
+        }
+    }
+
+    #[test]
+    fn cas_no_nome() {
+        assert_eq!(
+            oferta("Calça NR10 CA 30977", None).cas_no_nome(),
+            vec![30977]
+        );
+        assert_eq!(
+            oferta("KIT CAMISA C.A 46298 + CALÇA C.A. 46296", None).cas_no_nome(),
+            vec![46298, 46296]
+        );
+        assert_eq!(
+            oferta("Calça Vectra Work CA 38561 / 41282", None).cas_no_nome(),
+            vec![38561, 41282]
+        );
+        assert_eq!(oferta("Luva CA: 445 nº", None).cas_no_nome(), vec![445]);
+        assert_eq!(oferta("Luva CA nº 445", None).cas_no_nome(), vec![445]);
+        assert!(oferta("Calça Câmara Fria Nylon - G", None)
+            .cas_no_nome()
+            .is_empty());
+        assert_eq!(
+            oferta("Calça Therm-Fire 018 CA 4", Some(45108)).cas_no_nome(),
+            Vec::<u32>::new()
+        );
+        assert_eq!(
+            oferta("Calça Therm-Fire 018 CA 4", Some(4)).cas_no_nome(),
+            vec![4]
+        );
+        // o número cortado só é ignorado no fim do nome e se for o começo
+        // do CA declarado
+        assert_eq!(
+            oferta("Calça Therm-Fire CA 4 Cinza", Some(45108)).cas_no_nome(),
+            vec![4]
+        );
+        assert_eq!(
+            oferta("Calça Therm-Fire 018 CA 9", Some(45108)).cas_no_nome(),
+            vec![9]
+        );
+        assert_eq!(
+            oferta("Calça Therm-Fire 018 CA 4", None).cas_no_nome(),
+            vec![4]
+        );
+        // números que não são CAs
+        for nome in [
+            "Calça NR10 Tam 44 Risco 2",
+            "Respirador PFF2 8820 com válvula",
+            "Macacão Tyvek CAT III 500",
+            "Camisa 100% algodão 180g",
+            "Capacete Aba Frontal Classe B 2024",
+        ] {
+            assert!(oferta(nome, Some(45108)).cas_no_nome().is_empty(), "{nome}");
+        }
+        assert_eq!(
+            oferta("Bota PVC 38/40 CA 12345 tamanho 40", Some(12345)).cas_no_nome(),
+            vec![12345]
+        );
+    }
+
+    #[test]
+    fn tipo_do_produto() {
+        for (descricao, nome) in [
+            ("LUVA DE SEGURANÇA", "Luvas Nitrílicas Verde"),
+            ("LUVAS", "Luva de Vaqueta"),
+            ("ÓCULOS", "Oculos de Proteção Incolor"),
+            ("RESPIRADOR PURIFICADOR DE AR", "Respiradores PFF2"),
+            ("CINTURÃO DE SEGURANÇA", "Cinturões Paraquedista"),
+            ("AVENTAL", "Aventais de Raspa"),
+            ("CALÇA", "KIT CAMISA + CALÇAS"),
+            ("PROTETOR AUDITIVO", "Protetores auriculares"),
+        ] {
+            assert!(mesmo_tipo(descricao, nome), "{descricao} x {nome}");
+        }
+        for (descricao, nome) in [
+            ("CALÇA", "Calçado de Segurança"),
+            ("CALÇA", "Lixeira 360L"),
+            ("BOTINA", "Bota de PVC"),
+            ("LUVA", "Luvaria Central"),
+            ("CAPACETE", "Capa de Chuva"),
+        ] {
+            assert!(!mesmo_tipo(descricao, nome), "{descricao} x {nome}");
+        }
+        assert!(mesmo_tipo("", "Qualquer coisa"));
+    }
+
+    #[test]
+    fn classificacao() {
+        let ca = CA::builder(32551).descricao("CALÇA").build();
+        let classificar = |nome, declarado| oferta(nome, declarado).classificar(&ca);
+        assert_eq!(
+            classificar("Calça NR10 CA 30977", Some(30977)),
+            Classificacao::Consistente
+        );
+        assert_eq!(
+            classificar("Calça eletricista", Some(32551)),
+            Classificacao::Consistente
+        );
+        assert_eq!(
+            classificar("CALCA NR-10 C.A 32551", None),
+            Classificacao::Consistente
+        );
+        assert_eq!(
+            classificar("Calça de Chuva CA 37356", Some(37536)),
+            Classificacao::Suspeita(
+                "o nome cita o CA 37356, mas a loja declara o CA 37536".to_string()
+            )
+        );
+        assert_eq!(
+            classificar("Lixeira 360L", Some(32551)),
+            Classificacao::Suspeita(
+                "usa o CA 32551, mas o produto não é do tipo 'CALÇA'".to_string()
+            )
+        );
+        assert_eq!(
+            classificar("Lixeira 360L", Some(11434)),
+            Classificacao::NaoRelacionada
+        );
+        assert_eq!(
+            oferta("Lixeira 360L", Some(11434)).classificar(&CA::builder(32551).build()),
+            Classificacao::Consistente
+        );
+    }
+
+    #[tokio::test]
+    async fn classificacao_das_ofertas_da_pagina() {
+        let body = Html::parse_document(SUCESSO);
+        let (ca, _) = CA::consultar(&body, 32551).await.unwrap();
+        let ofertas = ofertas(&body);
+        let com_classificacao = |classificacao: &Classificacao| {
+            ofertas
+                .iter()
+                .filter(|o| &o.classificar(&ca) == classificacao)
+                .map(|o| o.nome.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            com_classificacao(&Classificacao::NaoRelacionada),
+            vec![
+                "Conjunto Motoqueiro moto race Brascamp - M",
+                "Protetor solar repelente fps 30 1/3 nutriex 120ml uva e uvb",
+                "Lixeira 360L",
+                "Protetor solar sun maxi 30 fps",
+            ]
+        );
+        let suspeitas = ofertas
+            .iter()
+            .filter(|o| matches!(o.classificar(&ca), Classificacao::Suspeita(_)))
+            .map(|o| o.nome.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            suspeitas,
+            vec!["Calça de Chuva em PVC Preta Maicol Com Ajuste de Cordão CA 37356"]
+        );
+    }
+
+    #[test]
+    fn ofertas_da_pagina() {
+        let ofertas = ofertas(&Html::parse_document(SUCESSO));
+        assert_eq!(ofertas.len(), 40);
+        assert_eq!(
+            ofertas[0],
+            Oferta {
+                id: Some(2288),
+                loja: "SUPER EPI".to_string(),
+                nome: "Calça NR10 Risco 2 ATPV 11 Cinza Anti-Chama Guardian DX Para Arco Elétrico Refletivo CA 30977".to_string(),
+                ca: Some(30977),
+                preco_de: Some(Decimal::new(23751, 2)),
+                preco_por: Some(Decimal::new(21851, 2)),
+                link: Some("https://buscaepi.com/acessar-oferta/2288?medium=consultaca".to_string()),
+            }
+        );
+        let lixeira = ofertas.iter().find(|o| o.nome == "Lixeira 360L").unwrap();
+        assert_eq!(lixeira.loja, "RIO EPI");
+        assert_eq!(lixeira.ca, Some(11434));
+        assert_eq!(lixeira.preco_de, None);
+        assert_eq!(lixeira.preco_por, Some(Decimal::new(97777, 2)));
+    }
+}
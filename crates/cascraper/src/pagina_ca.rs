@@ -0,0 +1,431 @@
+/*!
+Divide a página do CA em seções, para que cada informação seja lida
+da seção a que pertence.
+
+A página tem um cabeçalho (N° CA, situação, validade...) seguido de blocos
+com um `h3`:
+```html
+<div class="grupo_result_ca">
+  <h3>Fabricante</h3>
+  <p class="info"><strong>Razão Social:</strong><br />FARP INDUSTRIA DE ROUPAS LTDA</p>
+  ...
+</div>
+```
+Chaves como "Razão Social" aparecem em mais de uma seção (Fabricante e
+Laudos), então não dá para tratar a página como um único mapa chave/valor.
+*/
+use crate::util::normalizar;
+use scraper::{CaseSensitivity, ElementRef, Html, Node, Selector};
+use std::collections::HashMap;
+
+/// A página do CA dividida em seções.
+#[derive(Debug, Clone)]
+pub struct PaginaCA<'a> {
+    html: &'a Html,
+    cabecalho: Option<Secao<'a>>,
+    secoes: Vec<Secao<'a>>,
+}
+
+impl<'a> PaginaCA<'a> {
+    pub fn new(html: &'a Html) -> Self {
+        let num_ca_selector = Selector::parse("p.num_ca").unwrap();
+        let cabecalho = html
+            .select(&num_ca_selector)
+            .next()
+            .and_then(|p| p.parent())
+            .and_then(ElementRef::wrap)
+            .map(Secao::cabecalho);
+        let secao_selector = Selector::parse("div.grupo_result_ca").unwrap();
+        let h3_selector = Selector::parse("h3").unwrap();
+        let secoes = html
+            .select(&secao_selector)
+            .filter_map(|div| {
+                let h3 = div.select(&h3_selector).next()?;
+                Some(Secao::new(titulo_do_h3(h3), div))
+            })
+            .collect();
+        PaginaCA {
+            html,
+            cabecalho,
+            secoes,
+        }
+    }
+    /// O documento inteiro, para o que não pertence a nenhuma seção.
+    pub fn html(&self) -> &'a Html {
+        self.html
+    }
+    /// Os parágrafos do topo da página (N° CA, situação, validade, processo
+    /// e natureza). É `None` quando o CA não existe.
+    pub fn cabecalho(&self) -> Option<&Secao<'a>> {
+        self.cabecalho.as_ref()
+    }
+    /// Todas as seções com `h3`, na ordem da página.
+    pub fn secoes(&self) -> &[Secao<'a>] {
+        &self.secoes
+    }
+    /// Seção cujo título é `titulo`, sem diferenciar maiúsculas nem acentos
+    /// (`"historico"` encontra "Histórico").
+    pub fn secao(&self, titulo: &str) -> Option<&Secao<'a>> {
+        let titulo = normalizar(titulo);
+        self.secoes.iter().find(|s| normalizar(&s.titulo) == titulo)
+    }
+}
+
+/// Uma seção da página.
+#[derive(Debug, Clone)]
+pub struct Secao<'a> {
+    titulo: String,
+    elemento: ElementRef<'a>,
+    blocos: Vec<Vec<(String, String)>>,
+    paragrafos: Vec<String>,
+    listas: Vec<Vec<String>>,
+    tabelas: Vec<Tabela>,
+}
+
+impl<'a> Secao<'a> {
+    fn new(titulo: String, elemento: ElementRef<'a>) -> Self {
+        let mut blocos = vec![vec![]];
+        let mut paragrafos = vec![];
+        for descendente in elemento.descendants().filter_map(ElementRef::wrap) {
+            let valor = descendente.value();
+            if valor.name() == "p" && tem_chave(descendente) {
+                blocos.last_mut().unwrap().extend(par(descendente));
+            } else if valor.name() == "p" {
+                let texto = linhas(&texto_com_quebras(descendente));
+                if !texto.is_empty() {
+                    paragrafos.push(texto);
+                }
+            } else if valor.has_class("divider", CaseSensitivity::AsciiCaseInsensitive) {
+                blocos.push(vec![]);
+            }
+        }
+        blocos.retain(|bloco| !bloco.is_empty());
+        let lista_selector = Selector::parse("ul, ol").unwrap();
+        let li_selector = Selector::parse("li").unwrap();
+        let listas = elemento
+            .select(&lista_selector)
+            .map(|lista| {
+                lista
+                    .select(&li_selector)
+                    .map(|li| li.text().collect::<String>().trim().to_string())
+                    .collect()
+            })
+            .collect();
+        let table_selector = Selector::parse("table").unwrap();
+        let tabelas = elemento.select(&table_selector).map(Tabela::new).collect();
+        Secao {
+            titulo,
+            elemento,
+            blocos,
+            paragrafos,
+            listas,
+            tabelas,
+        }
+    }
+    /// O cabeçalho só tem parágrafos soltos, sem `div` nem `h3`. Lemos
+    /// apenas os `p` filhos diretos, para não misturar os textos das
+    /// caixas de favoritos e avaliação.
+    fn cabecalho(elemento: ElementRef<'a>) -> Self {
+        let pares = elemento
+            .child_elements()
+            .filter(|e| e.value().name() == "p")
+            .filter_map(par)
+            .collect::<Vec<_>>();
+        Secao {
+            titulo: String::new(),
+            elemento,
+            blocos: vec![pares],
+            paragrafos: vec![],
+            listas: vec![],
+            tabelas: vec![],
+        }
+    }
+    /// Texto do `h3` (vazio no cabeçalho).
+    pub fn titulo(&self) -> &str {
+        &self.titulo
+    }
+    /// O elemento HTML da seção, para o que o modelo não cobre.
+    pub fn elemento(&self) -> ElementRef<'a> {
+        self.elemento
+    }
+    /// Pares chave/valor na ordem da página.
+    /// Vêm de parágrafos como `<p><strong>Chave:</strong><br />valor</p>`.
+    pub fn pares(&self) -> impl Iterator<Item = &(String, String)> {
+        self.blocos.iter().flatten()
+    }
+    /// Pares agrupados pelos `div.divider` da seção. A seção "Laudos",
+    /// por exemplo, tem um bloco por laudo.
+    pub fn blocos(&self) -> &[Vec<(String, String)>] {
+        &self.blocos
+    }
+    /// Valor da primeira chave igual a `chave` (sem diferenciar maiúsculas).
+    pub fn valor(&self, chave: &str) -> Option<&str> {
+        let chave = chave.to_lowercase();
+        self.pares()
+            .find(|(c, _)| c.to_lowercase() == chave)
+            .map(|(_, v)| v.as_str())
+    }
+    /// Parágrafos que não são pares chave/valor. Os `<br>` viram `\n`.
+    pub fn paragrafos(&self) -> &[String] {
+        &self.paragrafos
+    }
+    /// Todo o texto da seção, sem o `h3`, com uma linha por `<br>` ou
+    /// por bloco (`p`, `div`, `li`...).
+    pub fn texto(&self) -> String {
+        linhas(&texto_com_quebras(self.elemento))
+    }
+    /// Itens de cada `ul`/`ol` da seção.
+    pub fn listas(&self) -> &[Vec<String>] {
+        &self.listas
+    }
+    pub fn tabelas(&self) -> &[Tabela] {
+        &self.tabelas
+    }
+    /// Pares da seção num mapa com as chaves em minúsculas.
+    pub(crate) fn mapa(&self) -> HashMap<String, String> {
+        mapa(self.pares())
+    }
+}
+
+/// Uma tabela de uma seção.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tabela {
+    /// Textos dos `th`.
+    pub cabecalho: Vec<String>,
+    /// Textos dos `td` de cada linha que tem `td`.
+    pub linhas: Vec<Vec<String>>,
+}
+
+impl Tabela {
+    fn new(table: ElementRef) -> Self {
+        let tr_selector = Selector::parse("tr").unwrap();
+        let th_selector = Selector::parse("th").unwrap();
+        let td_selector = Selector::parse("td").unwrap();
+        let texto = |e: ElementRef| e.text().collect::<String>().trim().to_string();
+        let mut tabela = Tabela::default();
+        for tr in table.select(&tr_selector) {
+            let tds = tr.select(&td_selector).map(texto).collect::<Vec<_>>();
+            if tds.is_empty() {
+                tabela.cabecalho.extend(tr.select(&th_selector).map(texto));
+            } else {
+                tabela.linhas.push(tds);
+            }
+        }
+        tabela
+    }
+}
+
+/// Interpreta um parágrafo na forma chave:valor:
+/// ```html
+/// <p>
+///     <strong>N° Processo:</strong>
+///     <br>
+///     19980274164202499
+/// </p>
+/// ```
+/// Parágrafos sem texto depois da chave (`<strong>Avaliação Geral:</strong>`)
+/// são descartados.
+fn par(paragrafo: ElementRef) -> Option<(String, String)> {
+    if !tem_chave(paragrafo) {
+        return None;
+    }
+    let texto = paragrafo.text().collect::<String>();
+    let (chave, valor) = texto.split_once(':')?;
+    let valor = valor.trim();
+    if valor.is_empty() {
+        return None;
+    }
+    Some((chave.trim().to_string(), valor.to_string()))
+}
+
+/// Diz se o parágrafo começa com um `strong` terminado em `:`.
+fn tem_chave(paragrafo: ElementRef) -> bool {
+    paragrafo.child_elements().next().is_some_and(|strong| {
+        strong.value().name() == "strong" && strong.text().collect::<String>().trim().ends_with(':')
+    })
+}
+
+/// Texto do elemento com `\n` no lugar de cada `<br>` e em volta de cada
+/// elemento de bloco. Títulos e scripts ficam de fora.
+fn texto_com_quebras(elemento: ElementRef) -> String {
+    let mut texto = String::new();
+    for filho in elemento.children() {
+        match filho.value() {
+            Node::Text(t) => texto.push_str(t),
+            Node::Element(e) => match e.name() {
+                "br" => texto.push('\n'),
+                "h1" | "h2" | "h3" | "script" | "style" => {}
+                nome => {
+                    let interno = texto_com_quebras(ElementRef::wrap(filho).unwrap());
+                    let bloco = ["p", "div", "li", "ul", "ol", "table", "tr"].contains(&nome);
+                    if bloco {
+                        texto.push('\n');
+                    }
+                    texto.push_str(&interno);
+                    if bloco {
+                        texto.push('\n');
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+    texto
+}
+
+/// Tira os espaços repetidos de cada linha e descarta as linhas vazias.
+fn linhas(texto: &str) -> String {
+    texto
+        .lines()
+        .map(|linha| linha.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|linha| !linha.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Junta pares num mapa com as chaves em minúsculas. Se uma chave se
+/// repetir, fica o primeiro valor.
+pub(crate) fn mapa<'p, I: Iterator<Item = &'p (String, String)>>(
+    pares: I,
+) -> HashMap<String, String> {
+    let mut resultado = HashMap::new();
+    for (chave, valor) in pares {
+        resultado
+            .entry(chave.to_lowercase())
+            .or_insert_with(|| valor.clone());
+    }
+    resultado
+}
+
+/// Título da seção. Alguns `h3` trazem um link à direita
+/// (`<span class="title-left">...</span><a>...</a>`); nesse caso só o
+/// `span.title-left` conta.
+fn titulo_do_h3(h3: ElementRef) -> String {
+    let span_selector = Selector::parse("span.title-left").unwrap();
+    let elemento = h3.select(&span_selector).next().unwrap_or(h3);
+    elemento
+        .text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pagina::SUCESSO;
+
+    #[test]
+    fn pares_de_paragrafos() {
+        let html = r#"
+    <!DOCTYPE html>
+    <meta charset="utf-8">
+    <div class="grupo_result_ca">
+     <h3>Seção</h3>
+     <p>
+         <strong>N° Processo:</strong>
+         <br>
+         19980274164202499
+     </p>
+     <p><strong>Situação:</strong><br><span style="color: rgb(255, 0, 0); font-weight: bold; --darkreader-inline-color: #ff1a1a;" data-darkreader-inline-color="">VENCIDO</span></p>
+     <p>Texto solto: não é um par.</p>
+     <p><strong>Vazio:</strong></p>
+    </div>
+"#;
+        let documento = Html::parse_document(html);
+        let pagina = PaginaCA::new(&documento);
+        let secao = pagina.secao("seção").unwrap();
+        assert_eq!(
+            secao.mapa(),
+            HashMap::from([
+                ("n° processo".to_string(), "19980274164202499".to_string()),
+                ("situação".to_string(), "VENCIDO".to_string())
+            ])
+        );
+        assert_eq!(secao.paragrafos(), ["Texto solto: não é um par."]);
+    }
+
+    #[test]
+    fn paragrafos_com_quebras() {
+        let html = r#"<div class="grupo_result_ca"><h3>Descrição Completa</h3>
+            <p class='info'>Primeira linha;<br />segunda   linha.</p>
+            <p class='info'>Outro parágrafo.</p>
+        </div>"#;
+        let documento = Html::parse_document(html);
+        let pagina = PaginaCA::new(&documento);
+        let secao = pagina.secao("descrição completa").unwrap();
+        assert_eq!(
+            secao.paragrafos(),
+            ["Primeira linha;\nsegunda linha.", "Outro parágrafo."]
+        );
+        assert_eq!(
+            secao.texto(),
+            "Primeira linha;\nsegunda linha.\nOutro parágrafo."
+        );
+    }
+
+    #[test]
+    fn secoes_da_pagina() {
+        let documento = Html::parse_document(SUCESSO);
+        let pagina = PaginaCA::new(&documento);
+        let titulos = pagina
+            .secoes()
+            .iter()
+            .map(|s| s.titulo())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            titulos,
+            vec![
+                "Ofertas BuscaEPI relacionadas a este EPI",
+                "Descrição Completa",
+                "Fabricante",
+                "Dados Complementares",
+                "Laudos",
+                "Normas",
+                "Histórico",
+                "Dúvidas sobre este CA",
+            ]
+        );
+
+        let cabecalho = pagina.cabecalho().unwrap();
+        assert_eq!(cabecalho.valor("N° CA"), Some("32551"));
+        assert_eq!(cabecalho.valor("natureza"), Some("Nacional"));
+        assert_eq!(cabecalho.valor("razão social"), None);
+
+        // "Razão Social" existe nas duas seções, com valores diferentes.
+        assert_eq!(
+            pagina.secao("fabricante").unwrap().valor("razão social"),
+            Some("FARP INDUSTRIA DE ROUPAS LTDA")
+        );
+        let laudos = pagina.secao("laudos").unwrap();
+        assert_eq!(laudos.blocos().len(), 2);
+        assert_eq!(laudos.valor("razão social"), Some("SENAI CETIQT"));
+
+        let normas = pagina.secao("normas").unwrap();
+        assert_eq!(normas.listas()[0].len(), 5);
+        let historico = pagina.secao("historico").unwrap();
+        assert_eq!(historico.tabelas()[0].cabecalho.len(), 2);
+        assert_eq!(historico.tabelas()[0].linhas.len(), 9);
+        assert_eq!(
+            historico.tabelas()[0].linhas[0],
+            vec!["21/03/2013".to_string(), "Expedido".to_string()]
+        );
+        assert_eq!(
+            pagina
+                .secao("DESCRIÇÃO COMPLETA")
+                .unwrap()
+                .paragrafos()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn pagina_sem_ca() {
+        let documento = Html::parse_document("");
+        let pagina = PaginaCA::new(&documento);
+        assert!(pagina.cabecalho().is_none());
+        assert!(pagina.secoes().is_empty());
+    }
+}
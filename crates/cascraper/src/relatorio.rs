@@ -0,0 +1,67 @@
+/*!
+Relatório dos problemas encontrados durante a extração de um CA.
+*/
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Problemas encontrados ao extrair um CA. É retornado junto com o CA
+/// por `CA::consultar`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtracaoRelatorio {
+    pub problemas: Vec<Problema>,
+    /// Quantas requisições foram feitas para baixar a página, contando as
+    /// repetidas. Fica em 0 quando a página não foi baixada pelo
+    /// `ClienteConsultaCA` (por exemplo, em `CA::consultar`).
+    #[serde(default)]
+    pub tentativas: u32,
+}
+
+impl ExtracaoRelatorio {
+    /// Diz se a extração ocorreu sem problemas. As tentativas não contam.
+    pub fn esta_vazio(&self) -> bool {
+        self.problemas.is_empty()
+    }
+
+    /// Retorna os problemas de um campo (`"processo"`, `"fabricante.cnpj"`, ...).
+    pub fn do_campo<'a>(&'a self, campo: &'a str) -> impl Iterator<Item = &'a Problema> {
+        self.problemas.iter().filter(move |p| p.campo == campo)
+    }
+}
+
+/// Um problema na extração de um campo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Problema {
+    /// Nome do campo no struct `CA` (`"validade"`, `"laudos[1].cnpj"`, ...).
+    pub campo: String,
+    pub tipo: TipoProblema,
+    /// Valor bruto encontrado na página, se houver.
+    pub valor: Option<String>,
+}
+
+/// Tipo de problema encontrado na extração.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TipoProblema {
+    /// A informação não foi encontrada na página.
+    Ausente,
+    /// A informação foi encontrada, mas está sem texto.
+    Vazio,
+    /// A informação não pôde ser interpretada. Contém a mensagem do erro.
+    Invalido(String),
+    /// A informação contradiz outra parte da página. Contém a explicação.
+    Divergente(String),
+}
+
+impl fmt::Display for Problema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.tipo {
+            TipoProblema::Ausente => write!(f, "{} não encontrado", self.campo)?,
+            TipoProblema::Vazio => write!(f, "{} encontrado, mas sem texto", self.campo)?,
+            TipoProblema::Invalido(erro) => write!(f, "{}: erro no parsing ({erro})", self.campo)?,
+            TipoProblema::Divergente(motivo) => write!(f, "{}: {motivo}", self.campo)?,
+        }
+        if let Some(valor) = &self.valor {
+            write!(f, " [valor: '{valor}']")?;
+        }
+        Ok(())
+    }
+}
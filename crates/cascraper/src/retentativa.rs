@@ -0,0 +1,276 @@
+/*!
+Política de novas tentativas para as requisições ao site do ConsultaCA.
+*/
+use crate::errors::CAError;
+use chrono::{DateTime, Utc};
+use log::warn;
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::future::Future;
+use std::time::Duration;
+
+/// Quantas vezes e com que intervalo uma requisição que falhou é repetida.
+///
+/// A espera antes da tentativa `n + 1` é `espera_inicial * 2^(n - 1)`,
+/// limitada a `espera_maxima`, e varia aleatoriamente entre a metade e o
+/// total desse valor, para que vários clientes não voltem ao mesmo tempo.
+/// Se o site mandar `Retry-After`, esperamos pelo menos o que ele pede,
+/// mesmo que passe de `espera_maxima`; só desistimos se ele pedir mais do
+/// que `espera_maxima_retry_after`.
+///
+/// Só os erros temporários (veja `CAError::e_temporario`) são repetidos.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoliticaRetentativa {
+    /// Número máximo de tentativas, contando a primeira.
+    pub tentativas: u32,
+    pub espera_inicial: Duration,
+    pub espera_maxima: Duration,
+    /// Maior espera pedida pelo site (`Retry-After`) que aceitamos.
+    pub espera_maxima_retry_after: Duration,
+}
+
+impl Default for PoliticaRetentativa {
+    fn default() -> Self {
+        PoliticaRetentativa {
+            tentativas: 3,
+            espera_inicial: Duration::from_millis(500),
+            espera_maxima: Duration::from_secs(30),
+            espera_maxima_retry_after: Duration::from_secs(300),
+        }
+    }
+}
+
+impl PoliticaRetentativa {
+    /// Política que faz uma única tentativa.
+    pub fn nenhuma() -> Self {
+        PoliticaRetentativa {
+            tentativas: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Espera antes da próxima tentativa, depois de `falhas` falhas seguidas.
+    pub fn espera(&self, falhas: u32) -> Duration {
+        let fator = 2u32.saturating_pow(falhas.saturating_sub(1));
+        let teto = self
+            .espera_inicial
+            .saturating_mul(fator)
+            .min(self.espera_maxima);
+        let metade = teto / 2;
+        metade + rand::thread_rng().gen_range(Duration::ZERO..=teto - metade)
+    }
+
+    /// Executa `tentativa` até que ela dê certo, falhe com um erro que não
+    /// é temporário ou esgote as tentativas. Cada falha é registrada no log.
+    /// Em caso de sucesso, retorna também quantas tentativas foram feitas.
+    pub(crate) async fn executar<T, F, Fut>(&self, mut tentativa: F) -> Result<(T, u32), CAError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Falha>>,
+    {
+        let mut falhas = 0;
+        loop {
+            let falha = match tentativa().await {
+                Ok(v) => return Ok((v, falhas + 1)),
+                Err(falha) => falha,
+            };
+            falhas += 1;
+            if !falha.erro.e_temporario() || falhas >= self.tentativas {
+                if falhas > 1 {
+                    warn!("{} Desistindo após {falhas} tentativas.", falha.erro);
+                }
+                return Err(falha.erro);
+            }
+            let mut espera = self.espera(falhas);
+            if let Some(pedida) = falha.retry_after {
+                if pedida > self.espera_maxima_retry_after {
+                    warn!(
+                        "{} O site pediu para esperar {}s (Retry-After), mais do que o máximo de {}s.",
+                        falha.erro,
+                        pedida.as_secs(),
+                        self.espera_maxima_retry_after.as_secs()
+                    );
+                    return Err(falha.erro);
+                }
+                espera = espera.max(pedida);
+            }
+            warn!(
+                "{} Tentativa {falhas} de {}; tentando de novo em {}ms.",
+                falha.erro,
+                self.tentativas,
+                espera.as_millis()
+            );
+            tokio::time::sleep(espera).await;
+        }
+    }
+}
+
+/// Uma tentativa que falhou e, se o site informou, quanto esperar antes
+/// da próxima.
+#[derive(Debug)]
+pub(crate) struct Falha {
+    pub erro: CAError,
+    pub retry_after: Option<Duration>,
+}
+
+impl From<CAError> for Falha {
+    fn from(erro: CAError) -> Self {
+        Falha {
+            erro,
+            retry_after: None,
+        }
+    }
+}
+
+/// Lê o cabeçalho `Retry-After`, que pode ser um número de segundos
+/// (`"120"`) ou uma data (`"Wed, 21 Oct 2015 07:28:00 GMT"`).
+pub(crate) fn retry_after(cabecalhos: &HeaderMap, agora: DateTime<Utc>) -> Option<Duration> {
+    let valor = cabecalhos.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(segundos) = valor.parse::<u64>() {
+        return Some(Duration::from_secs(segundos));
+    }
+    let data = DateTime::parse_from_rfc2822(valor).ok()?;
+    Some(
+        (data.with_timezone(&Utc) - agora)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use std::cell::Cell;
+
+    #[test]
+    fn espera_exponencial() {
+        let politica = PoliticaRetentativa {
+            tentativas: 10,
+            espera_inicial: Duration::from_millis(100),
+            espera_maxima: Duration::from_secs(1),
+            ..Default::default()
+        };
+        for (falhas, teto) in [
+            (1, 100),
+            (2, 200),
+            (3, 400),
+            (4, 800),
+            (5, 1000),
+            (40, 1000),
+        ] {
+            let espera = politica.espera(falhas).as_millis();
+            assert!(
+                (teto / 2..=teto).contains(&espera),
+                "{falhas} falhas: {espera}ms"
+            );
+        }
+    }
+
+    #[test]
+    fn cabecalho_retry_after() {
+        let agora = DateTime::parse_from_rfc3339("2015-10-21T07:27:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let cabecalhos = |valor| {
+            let mut cabecalhos = HeaderMap::new();
+            cabecalhos.insert(RETRY_AFTER, HeaderValue::from_static(valor));
+            cabecalhos
+        };
+        assert_eq!(
+            retry_after(&cabecalhos("120"), agora),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            retry_after(&cabecalhos("Wed, 21 Oct 2015 07:28:00 GMT"), agora),
+            Some(Duration::from_secs(60))
+        );
+        // data no passado: pode tentar já
+        assert_eq!(
+            retry_after(&cabecalhos("Wed, 21 Oct 2015 07:00:00 GMT"), agora),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after(&cabecalhos("amanha"), agora), None);
+        assert_eq!(retry_after(&HeaderMap::new(), agora), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn so_repete_erros_temporarios() {
+        let politica = PoliticaRetentativa {
+            tentativas: 3,
+            espera_inicial: Duration::from_millis(1),
+            espera_maxima: Duration::from_millis(10),
+            espera_maxima_retry_after: Duration::from_secs(60),
+        };
+        let chamadas = Cell::new(0);
+        let resultado = politica
+            .executar(|| async {
+                chamadas.set(chamadas.get() + 1);
+                match chamadas.get() {
+                    1 => Err(Falha::from(CAError::TempoEsgotado(1))),
+                    2 => Err(Falha::from(CAError::StatusHttp(1, 503))),
+                    _ => Ok("ok"),
+                }
+            })
+            .await;
+        assert_eq!(resultado, Ok(("ok", 3)));
+        assert_eq!(chamadas.get(), 3);
+
+        chamadas.set(0);
+        let resultado: Result<((), u32), _> = politica
+            .executar(|| async {
+                chamadas.set(chamadas.get() + 1);
+                Err(Falha::from(CAError::StatusHttp(2, 502)))
+            })
+            .await;
+        assert_eq!(resultado, Err(CAError::StatusHttp(2, 502)));
+        assert_eq!(chamadas.get(), 3);
+
+        for erro in [CAError::NaoEncontrado(3), CAError::StatusHttp(3, 404)] {
+            chamadas.set(0);
+            let resultado: Result<((), u32), _> = politica
+                .executar(|| async {
+                    chamadas.set(chamadas.get() + 1);
+                    Err(Falha::from(erro.clone()))
+                })
+                .await;
+            assert_eq!(resultado, Err(erro));
+            assert_eq!(chamadas.get(), 1);
+        }
+
+        // Retry-After maior que a espera máxima, mas dentro do limite do
+        // cabeçalho: espera o que o site pediu
+        chamadas.set(0);
+        let inicio = tokio::time::Instant::now();
+        let resultado = politica
+            .executar(|| async {
+                chamadas.set(chamadas.get() + 1);
+                match chamadas.get() {
+                    1 => Err(Falha {
+                        erro: CAError::StatusHttp(4, 429),
+                        retry_after: Some(Duration::from_secs(45)),
+                    }),
+                    _ => Ok("ok"),
+                }
+            })
+            .await;
+        assert_eq!(resultado, Ok(("ok", 2)));
+        assert_eq!(inicio.elapsed(), Duration::from_secs(45));
+
+        // Retry-After maior que o limite do cabeçalho: desiste sem esperar
+        chamadas.set(0);
+        let inicio = tokio::time::Instant::now();
+        let resultado: Result<((), u32), _> = politica
+            .executar(|| async {
+                chamadas.set(chamadas.get() + 1);
+                Err(Falha {
+                    erro: CAError::StatusHttp(5, 429),
+                    retry_after: Some(Duration::from_secs(3600)),
+                })
+            })
+            .await;
+        assert_eq!(resultado, Err(CAError::StatusHttp(5, 429)));
+        assert_eq!(chamadas.get(), 1);
+        assert_eq!(inicio.elapsed(), Duration::ZERO);
+    }
+}
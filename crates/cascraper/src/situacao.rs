@@ -0,0 +1,45 @@
+/*!
+Situação de um CA conforme exibida na página ("VÁLIDO", "VENCIDO", ...).
+*/
+use serde::{Deserialize, Serialize};
+
+/// Situação atual do CA.
+/// Textos que ainda não conhecemos caem em `Desconhecido`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Situacao {
+    Valido,
+    Vencido,
+    Suspenso,
+    Cancelado,
+    Desconhecido(String),
+}
+
+impl From<&str> for Situacao {
+    fn from(texto: &str) -> Self {
+        let texto = texto.trim();
+        match texto.to_uppercase().as_str() {
+            "VÁLIDO" | "VALIDO" => Situacao::Valido,
+            "VENCIDO" => Situacao::Vencido,
+            "SUSPENSO" => Situacao::Suspenso,
+            "CANCELADO" => Situacao::Cancelado,
+            _ => Situacao::Desconhecido(texto.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Situacao;
+    #[test]
+    fn situacao_from_str() {
+        assert_eq!(Situacao::from("VÁLIDO"), Situacao::Valido);
+        assert_eq!(Situacao::from("valido"), Situacao::Valido);
+        assert_eq!(Situacao::from(" VENCIDO "), Situacao::Vencido);
+        assert_eq!(Situacao::from("SUSPENSO"), Situacao::Suspenso);
+        assert_eq!(Situacao::from("CANCELADO"), Situacao::Cancelado);
+        assert_eq!(
+            Situacao::from("EM ANÁLISE"),
+            Situacao::Desconhecido("EM ANÁLISE".to_string())
+        );
+    }
+}
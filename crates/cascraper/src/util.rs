@@ -1,21 +1,37 @@
-use core::error;
+/*!
+Funções auxiliares para comparar textos da página.
+*/
 
-/// Extrai números de uma `String`.
-pub fn extrair_numeros(a: String) -> Result<u64, Box<dyn error::Error>> {
-    Ok(a.chars()
-        .filter(|x| x.is_numeric())
-        .collect::<String>()
-        .parse::<u64>()?)
+/// Coloca o texto em minúsculas e remove os acentos do português, para que
+/// `"CALÇA"`, `"Calça"` e `"calca"` sejam comparados como iguais.
+pub(crate) fn normalizar(texto: &str) -> String {
+    texto
+        .to_lowercase()
+        .chars()
+        // acentos combinantes (texto em forma decomposta)
+        .filter(|c| !('\u{300}'..='\u{36f}').contains(c))
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            'ñ' => 'n',
+            c => c,
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::extrair_numeros;
+    use super::*;
+
     #[test]
-    fn test_extrair_numeros() {
-        assert_eq!(
-            extrair_numeros("69.561.137/0001-11".to_string()).unwrap(),
-            69561137000111
-        );
+    fn normalizacao() {
+        assert_eq!(normalizar("CALÇA"), "calca");
+        assert_eq!(normalizar("Câmara Fria"), "camara fria");
+        assert_eq!(normalizar("AÇÃO-ÉPICA"), "acao-epica");
+        assert_eq!(normalizar("na\u{303}o"), "nao");
     }
 }
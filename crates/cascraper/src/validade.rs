@@ -0,0 +1,126 @@
+/*!
+Validade de um CA. Nem todo CA tem uma data de validade: a validade dos EPIs
+certificados junto ao INMETRO é "condicionada à manutenção da certificação
+junto ao INMETRO" (veja o `modalInmetro` da página).
+*/
+use chrono::{FixedOffset, NaiveDate, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Validade do CA.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Validade {
+    /// O CA vale até essa data (inclusive).
+    Data(NaiveDate),
+    /// O CA vale enquanto o certificado do INMETRO permanecer válido.
+    CondicionadaInmetro,
+    /// A página não informa uma validade.
+    Indeterminada,
+}
+
+impl Validade {
+    /// Diz se a validade cobre a `data`.
+    /// `CondicionadaInmetro` sempre cobre (quem decide é a situação do CA)
+    /// e `Indeterminada` nunca cobre.
+    pub fn cobre(&self, data: NaiveDate) -> bool {
+        match self {
+            Validade::Data(validade) => data <= *validade,
+            Validade::CondicionadaInmetro => true,
+            Validade::Indeterminada => false,
+        }
+    }
+}
+
+impl TryFrom<&str> for Validade {
+    type Error = String;
+    /// Interpreta o texto do parágrafo "Validade", que pode ser algo como
+    /// `08/10/2026vencerá daqui 699 dias` ou
+    /// `Condicionada à manutenção da certificação junto ao INMETRO`.
+    fn try_from(texto: &str) -> Result<Self, Self::Error> {
+        let texto = texto.trim();
+        if let Some(Ok(data)) = texto
+            .get(..10)
+            .map(|a| NaiveDate::parse_from_str(a, "%d/%m/%Y"))
+        {
+            return Ok(Validade::Data(data));
+        }
+        let minusculo = texto.to_lowercase();
+        if minusculo.contains("inmetro") {
+            Ok(Validade::CondicionadaInmetro)
+        } else if minusculo.contains("indeterminad") {
+            Ok(Validade::Indeterminada)
+        } else {
+            Err(format!("validade desconhecida: '{texto}'"))
+        }
+    }
+}
+
+/// Fuso de Brasília (UTC-3, sem horário de verão desde 2019), usado pelo
+/// site para as datas de validade.
+const FUSO_BRASILIA: i32 = -3 * 3600;
+
+/// Data de hoje em Brasília. O site conta a validade nesse fuso, então
+/// usar o fuso da máquina adiantaria ou atrasaria a virada do dia.
+pub fn hoje() -> NaiveDate {
+    let fuso = FixedOffset::east_opt(FUSO_BRASILIA).unwrap();
+    Utc::now().with_timezone(&fuso).date_naive()
+}
+
+/// Extrai o número de dias de um texto como `vencerá daqui 699 dias`.
+/// Textos como `venceu há 10 dias` dão um número negativo.
+pub(crate) fn dias_para_vencer(texto: &str) -> Option<i64> {
+    let dias = texto
+        .chars()
+        .filter(|x| x.is_ascii_digit())
+        .collect::<String>()
+        .parse::<i64>()
+        .ok()?;
+    let texto = texto.to_lowercase();
+    if texto.contains("daqui") {
+        Some(dias)
+    } else if texto.contains("há") {
+        Some(-dias)
+    } else {
+        None
+    }
+}
+
+/// Diz se a `data` de validade está de acordo com os `dias` que faltam para
+/// o vencimento, contados a partir de `hoje`. Aceitamos um dia de diferença
+/// por causa do fuso horário do site.
+pub(crate) fn validade_confere(data: NaiveDate, dias: i64, hoje: NaiveDate) -> bool {
+    (data - hoje - TimeDelta::days(dias)).num_days().abs() <= 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn validade_try_from() {
+        assert_eq!(
+            Validade::try_from("08/10/2026vencerá daqui 699 dias"),
+            Ok(Validade::Data(
+                NaiveDate::from_ymd_opt(2026, 10, 8).unwrap()
+            ))
+        );
+        assert_eq!(
+            Validade::try_from("Condicionada à manutenção da certificação junto ao INMETRO"),
+            Ok(Validade::CondicionadaInmetro)
+        );
+        assert_eq!(
+            Validade::try_from("Indeterminada"),
+            Ok(Validade::Indeterminada)
+        );
+        assert!(Validade::try_from("ontem").is_err());
+        assert!(Validade::try_from("").is_err());
+    }
+    #[test]
+    fn dias() {
+        assert_eq!(dias_para_vencer("vencerá daqui 699 dias"), Some(699));
+        assert_eq!(dias_para_vencer("venceu há 3 dias"), Some(-3));
+        assert_eq!(dias_para_vencer("sem número"), None);
+        let hoje = NaiveDate::from_ymd_opt(2024, 11, 8).unwrap();
+        let data = NaiveDate::from_ymd_opt(2026, 10, 8).unwrap();
+        assert!(validade_confere(data, 699, hoje));
+        assert!(!validade_confere(data, 600, hoje));
+    }
+}
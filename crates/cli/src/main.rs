@@ -1,5 +1,10 @@
-use cascraper::pagina;
+use cascraper::{errors::CAError, ClienteConsultaCA, LimiteRequisicoes, Ordem};
 use clap::{builder::Styles, ArgAction, Parser};
+use futures_util::StreamExt;
+use log::LevelFilter;
+use log4rs::append::console::{ConsoleAppender, Target};
+use log4rs::config::{Appender, Config, Logger, Root};
+use log4rs::encode::pattern::PatternEncoder;
 #[derive(Parser, Debug)]
 #[command(
     author,
@@ -21,6 +26,16 @@ use clap::{builder::Styles, ArgAction, Parser};
 struct Args {
     #[arg(required = true)]
     cas: Vec<u32>,
+    #[arg(short = 'j', long, default_value_t = 4)]
+    #[arg(help = "Quantos CAs consultar ao mesmo tempo.")]
+    concorrencia: usize,
+    #[arg(long, default_value_t = LimiteRequisicoes::default().por_segundo)]
+    #[arg(help = "Máximo de requisições por segundo ao site.")]
+    por_segundo: f64,
+    #[arg(long, default_value_t = LimiteRequisicoes::default().rajada)]
+    #[arg(help = "Quantas requisições podem sair de uma vez depois de uma pausa.")]
+    rajada: u32,
+
     #[arg(action = ArgAction::Help, short, long)]
     #[arg(help = "Mostra essa mensagem e sai.")]
     help: Option<bool>,
@@ -30,20 +45,67 @@ struct Args {
     version: Option<bool>,
 }
 
+/// Mostra no stderr as novas tentativas feitas pelo cascraper. Os problemas
+/// de extração já são impressos a partir do relatório.
+fn iniciar_log() {
+    let stderr = ConsoleAppender::builder()
+        .target(Target::Stderr)
+        .encoder(Box::new(PatternEncoder::new("Aviso: {m}{n}")))
+        .build();
+    let config = Config::builder()
+        .appender(Appender::builder().build("stderr", Box::new(stderr)))
+        .logger(
+            Logger::builder()
+                .appender("stderr")
+                .build("cascraper::retentativa", LevelFilter::Warn),
+        )
+        .build(Root::builder().build(LevelFilter::Off))
+        .unwrap();
+    log4rs::init_config(config).unwrap();
+}
+
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
-    for ca in args.cas {
-        let client = reqwest::Client::new();
-        let body = pagina(Some(client), ca).await;
-        let consulta = match cascraper::CA::consultar(&body, ca).await {
+    iniciar_log();
+    let cliente = match ClienteConsultaCA::builder()
+        .limite(LimiteRequisicoes::new(args.por_segundo, args.rajada))
+        .build()
+    {
+        Ok(cliente) => cliente,
+        Err(e) => {
+            eprintln!("Erro: {e}");
+            std::process::exit(2);
+        }
+    };
+    let mut falhas = vec![];
+    let mut resultados =
+        cliente.consultar_lote_com_relatorio(args.cas, args.concorrencia, Ordem::Entrada);
+    while let Some((ca, resultado)) = resultados.next().await {
+        let (consulta, relatorio) = match resultado {
             Ok(c) => c,
-            Err(e) => panic!("{:#?}", e),
+            Err(CAError::NaoEncontrado(_)) => {
+                println!("CA {ca} não encontrado.");
+                continue;
+            }
+            Err(e) => {
+                eprintln!("Erro: {e}");
+                falhas.push(ca);
+                continue;
+            }
         };
-        if consulta.ca == 0 {
-            println!("CA {ca} não encontrado.");
-            continue;
-        }
         println!("{:#?}", consulta);
+        for problema in relatorio.problemas {
+            eprintln!("Aviso (CA {ca}): {problema}.");
+        }
+    }
+    if !falhas.is_empty() {
+        let falhas = falhas
+            .iter()
+            .map(|ca| ca.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        eprintln!("CAs que não puderam ser consultados: {falhas}.");
+        std::process::exit(1);
     }
 }